@@ -10,15 +10,147 @@ mod error;
 /// Contains the functions used to query the database.
 pub mod queries;
 
+#[cfg(feature = "metrics")]
+mod metrics;
+mod prepared_cache;
 mod stats_cache;
 use stats_cache::StatsCacheMessage;
 
 pub use error::Error;
+#[cfg(feature = "metrics")]
+pub use metrics::Metrics;
+pub use prepared_cache::{CacheSize, PreparedQuery, StatementCache};
 
 use actix::{spawn as actix_spawn, Addr, System};
-use futures::future::{err, ok, Either, Future};
+use bb8::{ManageConnection, Pool, RunError};
+use futures::future::{err, ok, Either, Future, IntoFuture};
+use native_tls::{Certificate, Identity, TlsConnector};
+use postgres_native_tls::MakeTlsConnector;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+#[cfg(feature = "metrics")]
+use std::time::Instant;
 use tokio::spawn as tokio_spawn;
-use tokio_postgres::{connect as pg_connect, Client, NoTls};
+use tokio_postgres::{Client, Config as PgConfig, NoTls};
+
+/// A `host`/`port` pair for the structured connection builder.
+#[derive(Clone, Debug)]
+struct Host {
+    host: &'static str,
+    port: u16,
+}
+
+/// The TLS connector stored on [`PgConnectionManager`]. Keeping it as a concrete enum (rather than
+/// making the manager generic over `MakeTlsConnect`) means the manager's `Connection` is a plain
+/// [`Client`], so [`Config::pool`] has a single type for both the plaintext and TLS cases.
+#[derive(Clone)]
+enum PoolConnector {
+    NoTls,
+    Tls(MakeTlsConnector),
+}
+
+/// A [`bb8`] connection manager that opens `tokio_postgres` connections, honoring the configured
+/// TLS mode. Both branches resolve to a plain [`Client`], so the pool itself is not generic over
+/// the TLS connector. The manager holds the full list of host `PgConfig`s (from `db_url`,
+/// `add_host`, and `hostaddr`), trying them in order and applying `Prefer` fallback, so pooled
+/// connections use the same multi-host/TLS semantics as [`Config::connect`].
+#[derive(Clone)]
+struct PgConnectionManager {
+    configs: Vec<PgConfig>,
+    connector: PoolConnector,
+    tls_mode: TlsMode,
+}
+
+impl PgConnectionManager {
+    /// Connects using a single `PgConfig`, applying the TLS connector and `Prefer` fallback. The
+    /// returned future is `Send` so it can be driven from the pool's worker threads.
+    fn connect_one(
+        config: PgConfig,
+        connector: PoolConnector,
+        tls_mode: TlsMode,
+    ) -> Box<dyn Future<Item = Client, Error = Error> + Send> {
+        match connector {
+            PoolConnector::NoTls => Box::new(Config::spawn_connection(config.connect(NoTls))),
+            PoolConnector::Tls(connector) => {
+                let fallback_config = config.clone();
+                let tls_future = Config::spawn_connection(config.connect(connector));
+
+                if tls_mode == TlsMode::Prefer {
+                    // `Prefer` falls back to a plaintext connection if the server rejects TLS.
+                    Box::new(tls_future.or_else(move |_tls_err| {
+                        Config::spawn_connection(fallback_config.connect(NoTls))
+                    }))
+                } else {
+                    Box::new(tls_future)
+                }
+            }
+        }
+    }
+}
+
+impl ManageConnection for PgConnectionManager {
+    type Connection = Client;
+    type Error = Error;
+
+    fn connect(&self) -> Box<dyn Future<Item = Client, Error = Error> + Send> {
+        // Try each configured host sequentially, surfacing the last error only if all fail.
+        let mut attempts = self.configs.clone().into_iter();
+        let first = match attempts.next() {
+            Some(config) => config,
+            None => {
+                return Box::new(err(Error::generate_error(
+                    "NO_CONNECTION_TARGET",
+                    "No host was configured to connect to.".to_string(),
+                )))
+            }
+        };
+
+        let mut chain = Self::connect_one(first, self.connector.clone(), self.tls_mode);
+        for config in attempts {
+            let connector = self.connector.clone();
+            let tls_mode = self.tls_mode;
+            chain = Box::new(
+                chain.or_else(move |_prev_err| Self::connect_one(config, connector, tls_mode)),
+            );
+        }
+
+        chain
+    }
+
+    fn is_valid(
+        &self,
+        client: Client,
+    ) -> Box<dyn Future<Item = Client, Error = (Error, Client)> + Send> {
+        // Liveness is tracked cheaply via `has_broken`; avoid a round-trip on every checkout.
+        Box::new(ok(client))
+    }
+
+    fn has_broken(&self, client: &mut Client) -> bool {
+        client.is_closed()
+    }
+}
+
+/// How the database connection should negotiate TLS, mirroring libpq's `sslmode`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TlsMode {
+    /// Never use TLS. Equivalent to the historical `NoTls` behavior.
+    Disable,
+    /// Try TLS first, but fall back to a plaintext connection if the server rejects it.
+    Prefer,
+    /// Require TLS, but do not verify the server certificate.
+    Require,
+    /// Require TLS and verify that the server certificate is signed by a trusted CA.
+    VerifyCa,
+    /// Require TLS, verify the CA, and verify that the certificate matches the `host`.
+    VerifyFull,
+}
+
+impl Default for TlsMode {
+    fn default() -> Self {
+        TlsMode::Disable
+    }
+}
 
 /// Configures the DB connection and API.
 #[derive(Clone, Debug)]
@@ -31,6 +163,38 @@ pub struct Config {
     /// When set to a positive integer `n`, automatically refresh the Table Stats cache every `n`
     /// seconds. Default: `0` (cache is never automatically reset).
     pub cache_reset_interval_seconds: u32,
+    /// Optional structured list of `host`/`port` pairs, tried in order on `connect()`. When empty,
+    /// the host/port are parsed from `db_url`. Populate via [`Config::add_host`].
+    hosts: Vec<Host>,
+    /// Optional numeric IP used directly to skip DNS resolution. The `host` (from `db_url` or
+    /// [`Config::add_host`]) is still sent as the TLS/SNI and certificate-verification name.
+    pub hostaddr: Option<IpAddr>,
+    /// The TLS negotiation mode used when connecting to Postgres. Default: `TlsMode::Disable`.
+    pub tls_mode: TlsMode,
+    /// Path to a PEM-encoded root CA certificate used to verify the server (for `VerifyCa` and
+    /// `VerifyFull`). When `None`, the system trust store is used.
+    pub tls_root_cert_path: Option<&'static str>,
+    /// Path to a PKCS#12 client identity (cert + key) bundle used for client authentication.
+    pub tls_client_identity_path: Option<&'static str>,
+    /// Password protecting the PKCS#12 client identity bundle.
+    pub tls_client_identity_password: &'static str,
+    /// The maximum number of connections the pool will keep open. Default: `10`.
+    pub pool_max_size: u32,
+    /// The minimum number of idle connections the pool attempts to keep warm. Default: `None`
+    /// (bb8 keeps `pool_max_size` idle connections).
+    pub pool_min_idle: Option<u32>,
+    /// How long `get_connection` waits for a free connection before erroring. Default: `30`.
+    pub connection_timeout_seconds: u64,
+    /// The prepared-statement caching strategy. Default: `CacheSize::Bounded(500)`.
+    pub statement_cache_size: CacheSize,
+    /// Live per-connection statement caches handed out by [`Config::new_statement_cache`], tracked
+    /// so [`Config::reset_cache`] can flush them when the table-stats cache is reset.
+    statement_caches: Arc<Mutex<Vec<StatementCache>>>,
+    /// Observability handle, present when the `metrics` feature is enabled.
+    #[cfg(feature = "metrics")]
+    metrics: Metrics,
+    /// Connection pool, constructed once via [`Config::build_pool`].
+    pool: Option<Pool<PgConnectionManager>>,
     /// Actor address for the Table Stats Cache.
     stats_cache_addr: Option<Addr<stats_cache::StatsCache>>,
 }
@@ -41,6 +205,20 @@ impl Default for Config {
             db_url: "",
             is_cache_table_stats: false,
             cache_reset_interval_seconds: 0,
+            hosts: vec![],
+            hostaddr: None,
+            tls_mode: TlsMode::Disable,
+            tls_root_cert_path: None,
+            tls_client_identity_path: None,
+            tls_client_identity_password: "",
+            pool_max_size: 10,
+            pool_min_idle: None,
+            connection_timeout_seconds: 30,
+            statement_cache_size: CacheSize::default(),
+            statement_caches: Arc::new(Mutex::new(vec![])),
+            #[cfg(feature = "metrics")]
+            metrics: Metrics::new(),
+            pool: None,
             stats_cache_addr: None,
         }
     }
@@ -69,6 +247,255 @@ impl Config {
         self
     }
 
+    /// Adds a `host`/`port` pair to the structured connection builder. Hosts are tried in order on
+    /// `connect()` until one succeeds, enabling primary/replica failover lists.
+    /// ```
+    /// use rust_postgres_rest::Config;
+    ///
+    /// let mut config = Config::new("postgresql://postgres@/postgres");
+    /// config.add_host("primary.db.internal", 5432);
+    /// config.add_host("replica.db.internal", 5432);
+    /// ```
+    pub fn add_host(&mut self, host: &'static str, port: u16) -> &mut Self {
+        self.hosts.push(Host { host, port });
+        self
+    }
+
+    /// Pins the numeric IP used to connect, skipping DNS resolution. The `host` name is still used
+    /// for TLS/SNI and certificate verification.
+    pub fn set_hostaddr(&mut self, hostaddr: IpAddr) -> &mut Self {
+        self.hostaddr = Some(hostaddr);
+        self
+    }
+
+    /// Builds the ordered list of `tokio_postgres::Config`s to try. When no structured hosts are
+    /// configured and no `hostaddr` is pinned, this is the single config parsed from `db_url`.
+    /// Otherwise a config per host is built programmatically, copying the credentials/dbname parsed
+    /// from `db_url` and applying the structured host/port and pinned `hostaddr`.
+    fn pg_configs(&self) -> Result<Vec<PgConfig>, Error> {
+        let base: PgConfig = self.db_url.parse().map_err(Error::from)?;
+
+        if self.hosts.is_empty() && self.hostaddr.is_none() {
+            return Ok(vec![base]);
+        }
+
+        // When no explicit hosts are given but a hostaddr is, fall back to the host parsed from the
+        // URL so the original name is still sent for verification.
+        let hosts: Vec<Host> = if self.hosts.is_empty() {
+            vec![Host {
+                host: self.host().unwrap_or(""),
+                port: base.get_ports().first().copied().unwrap_or(5432),
+            }]
+        } else {
+            self.hosts.clone()
+        };
+
+        let configs = hosts
+            .into_iter()
+            .map(|host_entry| {
+                let mut cfg = PgConfig::new();
+                if let Some(user) = base.get_user() {
+                    cfg.user(user);
+                }
+                if let Some(password) = base.get_password() {
+                    cfg.password(password);
+                }
+                if let Some(dbname) = base.get_dbname() {
+                    cfg.dbname(dbname);
+                }
+                cfg.host(host_entry.host);
+                cfg.port(host_entry.port);
+                if let Some(hostaddr) = self.hostaddr {
+                    cfg.hostaddr(hostaddr);
+                }
+                cfg
+            })
+            .collect();
+
+        Ok(configs)
+    }
+
+    /// Enables TLS for the database connection using the given mode.
+    /// ```
+    /// use rust_postgres_rest::{Config, TlsMode};
+    ///
+    /// let mut config = Config::new("postgresql://postgres@0.0.0.0:5432/postgres");
+    /// config.set_tls_mode(TlsMode::VerifyFull);
+    /// ```
+    pub fn set_tls_mode(&mut self, mode: TlsMode) -> &mut Self {
+        self.tls_mode = mode;
+        self
+    }
+
+    /// Sets the root CA certificate and (optionally) client identity used for TLS verification.
+    pub fn set_tls_certs(
+        &mut self,
+        root_cert_path: Option<&'static str>,
+        client_identity_path: Option<&'static str>,
+        client_identity_password: &'static str,
+    ) -> &mut Self {
+        self.tls_root_cert_path = root_cert_path;
+        self.tls_client_identity_path = client_identity_path;
+        self.tls_client_identity_password = client_identity_password;
+        self
+    }
+
+    /// Parses the `host` portion of `db_url`, used as the certificate/SNI verification name.
+    fn host(&self) -> Option<&'static str> {
+        self.db_url
+            .split("://")
+            .nth(1)
+            .and_then(|after_scheme| after_scheme.split('@').last())
+            .and_then(|after_userinfo| after_userinfo.split('/').next())
+            .and_then(|host_port| host_port.split(':').next())
+            .filter(|host| !host.is_empty())
+    }
+
+    /// Builds a `MakeTlsConnector` from the configured certificates and mode. Returns `None` when
+    /// TLS is disabled.
+    fn build_tls_connector(&self) -> Result<Option<MakeTlsConnector>, Error> {
+        if self.tls_mode == TlsMode::Disable {
+            return Ok(None);
+        }
+
+        let mut builder = TlsConnector::builder();
+
+        match self.tls_mode {
+            // `Require` accepts any certificate; only the channel is encrypted.
+            TlsMode::Require => {
+                builder.danger_accept_invalid_certs(true);
+                builder.danger_accept_invalid_hostnames(true);
+            }
+            // `VerifyCa` validates the chain but not the hostname.
+            TlsMode::VerifyCa => {
+                builder.danger_accept_invalid_hostnames(true);
+            }
+            // `VerifyFull` (and `Prefer` when it negotiates TLS) perform full verification.
+            _ => {}
+        }
+
+        if let Some(root_cert_path) = self.tls_root_cert_path {
+            let cert_bytes = std::fs::read(root_cert_path).map_err(Error::from)?;
+            let cert = Certificate::from_pem(&cert_bytes).map_err(Error::from)?;
+            builder.add_root_certificate(cert);
+        }
+
+        if let Some(identity_path) = self.tls_client_identity_path {
+            let identity_bytes = std::fs::read(identity_path).map_err(Error::from)?;
+            let identity =
+                Identity::from_pkcs12(&identity_bytes, self.tls_client_identity_password)
+                    .map_err(Error::from)?;
+            builder.identity(identity);
+        }
+
+        let connector = builder.build().map_err(Error::from)?;
+        Ok(Some(MakeTlsConnector::new(connector)))
+    }
+
+    /// Builds the connection pool once (typically at startup) and stores it on the `Config` so that
+    /// subsequent queries amortize connection setup cost. Honors `pool_max_size`, `pool_min_idle`,
+    /// `connection_timeout_seconds`, and the configured TLS mode.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use futures::future::Future;
+    /// use rust_postgres_rest::Config;
+    ///
+    /// let mut config = Config::new("postgresql://postgres@0.0.0.0:5432/postgres");
+    /// let fut = config.build_pool().map(|_| ());
+    /// tokio::run(fut.map_err(|e| panic!(e)));
+    /// ```
+    pub fn build_pool(&mut self) -> impl Future<Item = Config, Error = Error> {
+        let configs = match self.pg_configs() {
+            Ok(configs) => configs,
+            Err(e) => return Either::A(err(e)),
+        };
+
+        let connector = match self.build_tls_connector() {
+            Ok(Some(connector)) => PoolConnector::Tls(connector),
+            Ok(None) => PoolConnector::NoTls,
+            Err(e) => return Either::A(err(e)),
+        };
+
+        let manager = PgConnectionManager {
+            configs,
+            connector,
+            tls_mode: self.tls_mode,
+        };
+
+        let mut cfg = self.clone();
+        let pool_future = Pool::builder()
+            .max_size(self.pool_max_size)
+            .min_idle(self.pool_min_idle)
+            .connection_timeout(Duration::from_secs(self.connection_timeout_seconds))
+            .build(manager)
+            .map_err(Error::from)
+            .map(move |pool| {
+                cfg.pool = Some(pool);
+                cfg
+            });
+
+        Either::B(pool_future)
+    }
+
+    /// Runs `f` with a connection borrowed from the pool built by [`Config::build_pool`], returning
+    /// its result. This keeps connection setup cost off the per-request path. Errors if the pool
+    /// has not been built.
+    ///
+    /// When the `metrics` feature is enabled, the time from this call until `f` receives its
+    /// connection is recorded as the connection-acquire duration, and a `CONNECTION_POOL_*` error
+    /// is counted against its code in the `errors_total` series.
+    pub fn get_connection<F, T, E, U>(&self, f: F) -> Box<dyn Future<Item = T, Error = Error>>
+    where
+        F: FnOnce(Client) -> U + Send + 'static,
+        U: IntoFuture<Item = (T, Client), Error = (E, Client)> + Send + 'static,
+        U::Future: Send + 'static,
+        T: Send + 'static,
+        E: Into<Error> + Send + 'static,
+    {
+        match &self.pool {
+            Some(pool) => {
+                #[cfg(feature = "metrics")]
+                let acquire_started = Instant::now();
+                #[cfg(feature = "metrics")]
+                let metrics = self.metrics.clone();
+                #[cfg(feature = "metrics")]
+                let timed_out_metrics = metrics.clone();
+
+                let f = move |client: Client| {
+                    #[cfg(feature = "metrics")]
+                    metrics.observe_connection_acquire(acquire_started);
+
+                    f(client)
+                };
+
+                Box::new(pool.run(f).map_err(move |run_err| match run_err {
+                    RunError::User(e) => e.into(),
+                    RunError::TimedOut => {
+                        #[cfg(feature = "metrics")]
+                        timed_out_metrics.record_error("CONNECTION_POOL_TIMED_OUT");
+
+                        Error::generate_error(
+                            "CONNECTION_POOL_TIMED_OUT",
+                            "Timed out while waiting for an available pooled connection."
+                                .to_string(),
+                        )
+                    }
+                }))
+            }
+            None => {
+                #[cfg(feature = "metrics")]
+                self.metrics.record_error("CONNECTION_POOL_NOT_INITIALIZED");
+
+                Box::new(err(Error::generate_error(
+                    "CONNECTION_POOL_NOT_INITIALIZED",
+                    "Call `Config::build_pool` before acquiring pooled connections.".to_string(),
+                )))
+            }
+        }
+    }
+
     /// A convenience wrapper around `tokio_postgres::connect`. Returns a future that evaluates to
     /// the database client connection.
     ///
@@ -90,8 +517,81 @@ impl Config {
     ///
     /// tokio::run(fut);
     /// ```
-    pub fn connect(&self) -> impl Future<Item = Client, Error = Error> {
-        pg_connect(self.db_url, NoTls)
+    pub fn connect(&self) -> Box<dyn Future<Item = Client, Error = Error>> {
+        let connector = match self.build_tls_connector() {
+            Ok(connector) => connector,
+            Err(e) => return Box::new(err(e)),
+        };
+
+        let configs = match self.pg_configs() {
+            Ok(configs) => configs,
+            Err(e) => return Box::new(err(e)),
+        };
+
+        let tls_mode = self.tls_mode;
+
+        // Try each host sequentially, returning the last error only if all of them fail.
+        let mut attempts = configs.into_iter();
+        let first = match attempts.next() {
+            Some(config) => config,
+            None => {
+                #[cfg(feature = "metrics")]
+                self.metrics.record_error("NO_CONNECTION_TARGET");
+
+                return Box::new(err(Error::generate_error(
+                    "NO_CONNECTION_TARGET",
+                    "No host was configured to connect to.".to_string(),
+                )));
+            }
+        };
+
+        let mut chain: Box<dyn Future<Item = Client, Error = Error>> =
+            Self::connect_with_config(first, connector.clone(), tls_mode);
+        for config in attempts {
+            let connector = connector.clone();
+            chain = Box::new(chain.or_else(move |_prev_err| {
+                Self::connect_with_config(config, connector, tls_mode)
+            }));
+        }
+
+        chain
+    }
+
+    /// Connects using a single `tokio_postgres::Config`, applying the TLS connector and `Prefer`
+    /// fallback semantics.
+    fn connect_with_config(
+        config: PgConfig,
+        connector: Option<MakeTlsConnector>,
+        tls_mode: TlsMode,
+    ) -> Box<dyn Future<Item = Client, Error = Error>> {
+        match connector {
+            // TLS disabled: keep the historical `NoTls` path.
+            None => Box::new(Self::spawn_connection(config.connect(NoTls))),
+            Some(connector) => {
+                let fallback_config = config.clone();
+                let tls_future = Self::spawn_connection(config.connect(connector));
+
+                if tls_mode == TlsMode::Prefer {
+                    // `Prefer` falls back to a plaintext connection if the server rejects TLS.
+                    Box::new(tls_future.or_else(move |_tls_err| {
+                        Self::spawn_connection(fallback_config.connect(NoTls))
+                    }))
+                } else {
+                    Box::new(tls_future)
+                }
+            }
+        }
+    }
+
+    /// Spawns the `Connection` future returned by `tokio_postgres::connect` onto the active
+    /// runtime (actix or tokio) and resolves to the `Client`.
+    fn spawn_connection<T>(
+        connect_future: impl Future<Item = (Client, T), Error = tokio_postgres::Error>,
+    ) -> impl Future<Item = Client, Error = Error>
+    where
+        T: Future<Item = (), Error = tokio_postgres::Error> + Send + 'static,
+    {
+        connect_future
             .map_err(Error::from)
             .and_then(|(client, connection)| {
                 let is_actix_result = std::panic::catch_unwind(|| {
@@ -109,14 +609,26 @@ impl Config {
     }
 
     /// Forces the Table Stats cache to reset/refresh new data.
+    ///
+    /// When the `metrics` feature is enabled, a failure to reset (cache disabled or never
+    /// initialized) is counted against its code in the `errors_total` series.
     pub fn reset_cache(&self) -> impl Future<Item = (), Error = Error> {
         if !self.is_cache_table_stats {
+            #[cfg(feature = "metrics")]
+            self.metrics.record_error("TABLE_STATS_CACHE_NOT_ENABLED");
+
             return Either::A(err(Error::generate_error(
                 "TABLE_STATS_CACHE_NOT_ENABLED",
                 "".to_string(),
             )));
         }
 
+        // A table-stats reset can invalidate prepared statements that reference columns that were
+        // since dropped or retyped, so flush every live statement cache alongside it.
+        for cache in self.statement_caches.lock().unwrap().iter() {
+            cache.flush();
+        }
+
         match &self.stats_cache_addr {
             Some(addr) => {
                 let reset_cache_future = addr
@@ -128,13 +640,52 @@ impl Config {
                     });
                 Either::B(reset_cache_future)
             }
-            None => Either::A(err(Error::generate_error(
-                "TABLE_STATS_CACHE_NOT_INITIALIZED",
-                "The cache to be reset was not found.".to_string(),
-            ))),
+            None => {
+                #[cfg(feature = "metrics")]
+                self.metrics.record_error("TABLE_STATS_CACHE_NOT_INITIALIZED");
+
+                Either::A(err(Error::generate_error(
+                    "TABLE_STATS_CACHE_NOT_INITIALIZED",
+                    "The cache to be reset was not found.".to_string(),
+                )))
+            }
         }
     }
 
+    /// Sets the prepared-statement caching strategy.
+    /// ```
+    /// use rust_postgres_rest::{CacheSize, Config};
+    ///
+    /// let mut config = Config::new("postgresql://postgres@0.0.0.0:5432/postgres");
+    /// config.set_statement_cache_size(CacheSize::Bounded(1000));
+    /// ```
+    pub fn set_statement_cache_size(&mut self, size: CacheSize) -> &mut Self {
+        self.statement_cache_size = size;
+        self
+    }
+
+    /// Creates a fresh per-connection prepared-statement cache using the configured strategy. Each
+    /// pooled connection owns one of these; query handlers route `prepare` calls through it. A
+    /// clone is retained so [`Config::reset_cache`] can flush statements that a schema change may
+    /// have invalidated; orphaned caches (whose connection has been discarded) are pruned here so
+    /// the registry does not grow without bound.
+    pub fn new_statement_cache(&self) -> StatementCache {
+        let cache = StatementCache::new(self.statement_cache_size);
+
+        let mut caches = self.statement_caches.lock().unwrap();
+        caches.retain(|cache| !cache.is_orphaned());
+        caches.push(cache.clone());
+
+        cache
+    }
+
+    /// Returns the metrics handle so the embedding app can scrape it for a `/metrics` endpoint.
+    /// Available when the `metrics` feature is enabled.
+    #[cfg(feature = "metrics")]
+    pub fn metrics_handle(&self) -> Metrics {
+        self.metrics.clone()
+    }
+
     /// Set the interval timer to automatically reset the table stats cache. If this is not set, the
     /// cache is never reset.
     /// ```
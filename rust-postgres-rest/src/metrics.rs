@@ -0,0 +1,167 @@
+//! Optional observability subsystem, gated behind the `metrics` feature.
+//!
+//! Records counters and histograms for query behavior (rows returned/affected, latency,
+//! connection-acquire time, error counts bucketed by the crate's `Error` code strings), plus a
+//! gauge of in-flight queries and table-stats cache hit/miss counters. The embedding app mounts a
+//! `/metrics` endpoint by scraping the [`prometheus::Registry`] returned by [`Metrics::registry`]
+//! on the handle from [`crate::Config::metrics_handle`].
+
+use prometheus::{
+    Histogram, HistogramOpts, IntCounter, IntCounterVec, IntGauge, Opts, Registry,
+};
+use std::sync::Arc;
+use std::time::Instant;
+
+/// A handle to the metrics registry, cheaply cloneable and safe to share across threads.
+#[derive(Clone)]
+pub struct Metrics {
+    inner: Arc<MetricsInner>,
+}
+
+struct MetricsInner {
+    registry: Registry,
+    rows_returned: IntCounter,
+    rows_affected: IntCounter,
+    query_latency_seconds: Histogram,
+    connection_acquire_seconds: Histogram,
+    in_flight_queries: IntGauge,
+    errors_total: IntCounterVec,
+    stats_cache_hits: IntCounter,
+    stats_cache_misses: IntCounter,
+}
+
+impl Metrics {
+    /// Creates the metric collectors and registers them on a fresh registry.
+    pub fn new() -> Self {
+        let registry = Registry::new();
+
+        let rows_returned =
+            IntCounter::new("rows_returned_total", "Total rows returned by SELECT queries")
+                .unwrap();
+        let rows_affected = IntCounter::new(
+            "rows_affected_total",
+            "Total rows affected by INSERT/UPDATE/DELETE queries",
+        )
+        .unwrap();
+        let query_latency_seconds = Histogram::with_opts(HistogramOpts::new(
+            "query_latency_seconds",
+            "Query execution latency in seconds",
+        ))
+        .unwrap();
+        let connection_acquire_seconds = Histogram::with_opts(HistogramOpts::new(
+            "connection_acquire_seconds",
+            "Time spent acquiring a connection from the pool in seconds",
+        ))
+        .unwrap();
+        let in_flight_queries =
+            IntGauge::new("in_flight_queries", "Number of queries currently executing").unwrap();
+        let errors_total = IntCounterVec::new(
+            Opts::new("errors_total", "Total errors bucketed by error code"),
+            &["code"],
+        )
+        .unwrap();
+        let stats_cache_hits =
+            IntCounter::new("stats_cache_hits_total", "Table-stats cache hits").unwrap();
+        let stats_cache_misses =
+            IntCounter::new("stats_cache_misses_total", "Table-stats cache misses").unwrap();
+
+        registry.register(Box::new(rows_returned.clone())).unwrap();
+        registry.register(Box::new(rows_affected.clone())).unwrap();
+        registry
+            .register(Box::new(query_latency_seconds.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(connection_acquire_seconds.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(in_flight_queries.clone()))
+            .unwrap();
+        registry.register(Box::new(errors_total.clone())).unwrap();
+        registry.register(Box::new(stats_cache_hits.clone())).unwrap();
+        registry
+            .register(Box::new(stats_cache_misses.clone()))
+            .unwrap();
+
+        Metrics {
+            inner: Arc::new(MetricsInner {
+                registry,
+                rows_returned,
+                rows_affected,
+                query_latency_seconds,
+                connection_acquire_seconds,
+                in_flight_queries,
+                errors_total,
+                stats_cache_hits,
+                stats_cache_misses,
+            }),
+        }
+    }
+
+    /// Returns the registry so the embedding app can mount a `/metrics` endpoint.
+    pub fn registry(&self) -> &Registry {
+        &self.inner.registry
+    }
+
+    /// Wraps a query execution: increments the in-flight gauge, times the body, and records the
+    /// resulting row count. `count_rows` maps the query result to the number of rows it touched.
+    ///
+    /// Not yet called anywhere in this tree: the only concrete query-execution function present
+    /// here, `src/queries/insert_into_table.rs`'s `execute_insert`/`execute_copy_insert`, runs over
+    /// a synchronous `postgres::Transaction`, not the pooled `tokio_postgres::Client` this crate's
+    /// `Config`/`Metrics` are built around, so there is no connection-layer code in this snapshot
+    /// that holds both a `Metrics` handle and that transaction at once to wire them together.
+    pub fn observe_query<T, F>(&self, is_select: bool, count_rows: impl Fn(&T) -> u64, f: F) -> T
+    where
+        F: FnOnce() -> T,
+    {
+        self.inner.in_flight_queries.inc();
+        let timer = self.inner.query_latency_seconds.start_timer();
+
+        let result = f();
+
+        timer.observe_duration();
+        self.inner.in_flight_queries.dec();
+
+        let rows = count_rows(&result);
+        if is_select {
+            self.inner.rows_returned.inc_by(rows as i64);
+        } else {
+            self.inner.rows_affected.inc_by(rows as i64);
+        }
+
+        result
+    }
+
+    /// Records the time taken to acquire a pooled connection.
+    pub fn observe_connection_acquire(&self, started: Instant) {
+        self.inner
+            .connection_acquire_seconds
+            .observe(started.elapsed().as_secs_f64());
+    }
+
+    /// Increments the error counter for the given `Error` code string.
+    pub fn record_error(&self, code: &str) {
+        self.inner.errors_total.with_label_values(&[code]).inc();
+    }
+
+    /// Records a table-stats cache hit.
+    ///
+    /// Not yet called anywhere in this tree: `stats_cache.rs`, the module `lib.rs`'s
+    /// `mod stats_cache;` declares and where the actual get-or-fetch lookup would live, is not
+    /// part of this snapshot.
+    pub fn record_stats_cache_hit(&self) {
+        self.inner.stats_cache_hits.inc();
+    }
+
+    /// Records a table-stats cache miss. See [`Metrics::record_stats_cache_hit`] for why this has
+    /// no call site in this tree.
+    pub fn record_stats_cache_miss(&self) {
+        self.inner.stats_cache_misses.inc();
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,157 @@
+//! Per-connection cache of prepared statements, keyed by the generated SQL text.
+//!
+//! The query builders in [`crate::queries`] emit SQL dynamically from the request parameters, so
+//! the same logical endpoint produces the same SQL string on every call. Caching the resulting
+//! `tokio_postgres::Statement` avoids re-parsing and re-planning the statement on the server.
+
+use crate::Error;
+use futures::future::{ok, Either, Future};
+use lru::LruCache;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use tokio_postgres::{Client, Statement};
+
+/// The caching strategy used for prepared statements, selectable on [`crate::Config`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CacheSize {
+    /// Cache every distinct statement with no eviction.
+    Unbounded,
+    /// Do not cache; every query uses the unnamed-statement path.
+    Disabled,
+    /// Cache up to `n` statements, evicting the least-recently-used entry on overflow.
+    Bounded(usize),
+}
+
+impl Default for CacheSize {
+    fn default() -> Self {
+        // 500 distinct statements comfortably covers the endpoint/parameter permutations of a
+        // typical schema without unbounded growth.
+        CacheSize::Bounded(500)
+    }
+}
+
+/// Backing store for a single connection's prepared statements. `Unbounded` keeps a plain map;
+/// `Bounded` wraps an LRU; `Disabled` stores nothing.
+enum Store {
+    Unbounded(HashMap<String, Statement>),
+    Bounded(LruCache<String, Statement>),
+    Disabled,
+}
+
+/// What [`StatementCache::prepare`] hands back: either a named `Statement` fetched from (or
+/// inserted into) the cache, or the raw SQL text to run directly when caching is `Disabled`. The
+/// `Unnamed` variant exists because a `Statement` is itself a handle to a named, server-side
+/// prepared statement — returning one would force the prepare/close round-trip `Disabled` is
+/// meant to avoid. Callers should execute `Unnamed` SQL the same way they would execute `Prepared`,
+/// e.g. via `Client::query`/`execute`, both of which accept either a `&str` or a `&Statement`.
+pub enum PreparedQuery {
+    Prepared(Statement),
+    Unnamed(String),
+}
+
+/// A per-connection prepared-statement cache. Cheap to clone (shares the underlying store); the
+/// clone shares the same statements, so flushing one clone flushes them all. Backed by an
+/// `Arc<Mutex<_>>` so [`crate::Config`] can hold clones of every live cache and flush them when the
+/// table-stats cache is reset.
+#[derive(Clone)]
+pub struct StatementCache {
+    store: Arc<Mutex<Store>>,
+}
+
+impl fmt::Debug for StatementCache {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // The stored statements are not useful to print and locking here could deadlock; the
+        // existence of the cache is all a `Config` debug dump needs to convey.
+        f.debug_struct("StatementCache").finish()
+    }
+}
+
+impl StatementCache {
+    /// Creates a cache honoring the given [`CacheSize`].
+    pub fn new(size: CacheSize) -> Self {
+        let store = match size {
+            CacheSize::Unbounded => Store::Unbounded(HashMap::new()),
+            CacheSize::Bounded(n) => Store::Bounded(LruCache::new(n)),
+            CacheSize::Disabled => Store::Disabled,
+        };
+
+        StatementCache {
+            store: Arc::new(Mutex::new(store)),
+        }
+    }
+
+    /// Returns the `Statement` for `sql`, fetching it from the cache or preparing it on the
+    /// connection on a miss. When caching is `Disabled`, skips `prepare` entirely and returns the
+    /// raw SQL instead, so the caller runs it on the unnamed-statement path.
+    pub fn prepare(
+        &self,
+        client: &Client,
+        sql: &str,
+    ) -> impl Future<Item = PreparedQuery, Error = Error> {
+        if self.is_disabled() {
+            return Either::A(ok(PreparedQuery::Unnamed(sql.to_string())));
+        }
+
+        if let Some(statement) = self.get(sql) {
+            return Either::A(ok(PreparedQuery::Prepared(statement)));
+        }
+
+        let cache = self.clone();
+        let sql = sql.to_string();
+        Either::B(
+            client
+                .prepare(&sql)
+                .map_err(Error::from)
+                .map(move |statement| {
+                    cache.insert(sql, statement.clone());
+                    PreparedQuery::Prepared(statement)
+                }),
+        )
+    }
+
+    /// Whether this cache is configured as `Disabled`.
+    fn is_disabled(&self) -> bool {
+        match &*self.store.lock().unwrap() {
+            Store::Disabled => true,
+            _ => false,
+        }
+    }
+
+    /// Looks up a cached statement, recording LRU usage on a hit.
+    fn get(&self, sql: &str) -> Option<Statement> {
+        match &mut *self.store.lock().unwrap() {
+            Store::Unbounded(map) => map.get(sql).cloned(),
+            Store::Bounded(lru) => lru.get(sql).cloned(),
+            Store::Disabled => None,
+        }
+    }
+
+    fn insert(&self, sql: String, statement: Statement) {
+        match &mut *self.store.lock().unwrap() {
+            Store::Unbounded(map) => {
+                map.insert(sql, statement);
+            }
+            Store::Bounded(lru) => {
+                lru.put(sql, statement);
+            }
+            Store::Disabled => {}
+        }
+    }
+
+    /// Flushes every cached statement. Called when the table-stats cache is reset, since a column
+    /// set change can invalidate statements that reference dropped columns.
+    pub fn flush(&self) {
+        match &mut *self.store.lock().unwrap() {
+            Store::Unbounded(map) => map.clear(),
+            Store::Bounded(lru) => lru.clear(),
+            Store::Disabled => {}
+        }
+    }
+
+    /// Returns `true` when this is the last live handle to the underlying store — used to prune
+    /// caches belonging to connections the pool has discarded.
+    pub(crate) fn is_orphaned(&self) -> bool {
+        Arc::strong_count(&self.store) == 1
+    }
+}
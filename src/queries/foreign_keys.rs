@@ -1,17 +1,39 @@
-use super::table_stats::get_column_stats;
+use super::table_stats::{get_column_stats, get_reverse_column_stats};
 use crate::db::Connection;
 use crate::errors::ApiError;
-use sqlparser::{dialect::PostgreSqlDialect, sqlast::SQLStatement, sqlparser::Parser};
+use sqlparser::{
+    dialect::PostgreSqlDialect,
+    sqlast::{ASTNode, SQLOperator, SQLSetExpr, SQLStatement},
+    sqlparser::Parser,
+};
 use std::collections::HashMap;
 
-/// Converts a WHERE clause string into a vector of foreign key column strings.
-pub fn convert_where_clause_str_to_fk_columns(clause: &str) -> Result<Option<Vec<&str>>, ApiError> {
+/// Converts a WHERE clause string into a vector of the foreign key columns (dot-syntax identifiers
+/// like `another_foreign_key.nested_fk.some_int`) it references. Returns `Ok(None)` when the
+/// clause references no foreign key columns. The returned columns can be fed to
+/// [`ForeignKeyReference::from_query_columns`] so the JOIN machinery that powers SELECT embedding
+/// also rewrites WHERE predicates to reference the joined table aliases.
+///
+/// `table` is the base table the clause is being applied to; a compound identifier qualified with
+/// it (e.g. `a_table.column`) is an ordinary base-table column reference, not a dot-path FK
+/// traversal, and is excluded from the result.
+pub fn convert_where_clause_str_to_fk_columns(
+    table: &str,
+    clause: &str,
+) -> Result<Option<Vec<String>>, ApiError> {
     let full_statement = ["SELECT * FROM a_table WHERE ", clause].join("");
     let dialect = PostgreSqlDialect {};
     let ast = &Parser::parse_sql(&dialect, full_statement)?[0];
 
+    let mut fk_columns = vec![];
     match ast {
-        SQLStatement::SQLSelect(sql_query) => {}
+        SQLStatement::SQLSelect(sql_query) => {
+            if let SQLSetExpr::Select(select) = &sql_query.body {
+                if let Some(selection) = &select.selection {
+                    collect_fk_columns(selection, table, &mut fk_columns);
+                }
+            }
+        }
         SQLStatement::SQLInsert { .. } => {
             unimplemented!("There is no WHERE clause in an insert statement.")
         }
@@ -20,7 +42,191 @@ pub fn convert_where_clause_str_to_fk_columns(clause: &str) -> Result<Option<Vec
         _ => unimplemented!("Functionality not implemented."),
     };
 
-    Ok(None)
+    fk_columns.sort_unstable();
+    fk_columns.dedup();
+
+    if fk_columns.is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some(fk_columns))
+    }
+}
+
+/// Converts a WHERE clause that tests a table by the presence or absence of related rows into a
+/// correlated `EXISTS` / `NOT EXISTS` subquery. A clause like `NOT books.id` (or `books.id`) whose
+/// dot-path resolves to a foreign key relationship is rewritten against the referenced table;
+/// `NOT` produces `NOT EXISTS`. Returns `Ok(None)` when the clause references no foreign key
+/// columns, leaving the caller to fall back to the JOIN-based rewrite driven by
+/// [`convert_where_clause_str_to_fk_columns`].
+pub fn convert_where_clause_str_to_exists_subquery(
+    conn: &Connection,
+    table: &str,
+    clause: &str,
+) -> Result<Option<String>, ApiError> {
+    let full_statement = ["SELECT * FROM a_table WHERE ", clause].join("");
+    let dialect = PostgreSqlDialect {};
+    let ast = &Parser::parse_sql(&dialect, full_statement)?[0];
+
+    let selection = match ast {
+        SQLStatement::SQLSelect(sql_query) => match &sql_query.body {
+            SQLSetExpr::Select(select) => select.selection.as_ref(),
+            _ => None,
+        },
+        SQLStatement::SQLInsert { .. } => {
+            unimplemented!("There is no WHERE clause in an insert statement.")
+        }
+        SQLStatement::SQLUpdate { .. } => unimplemented!("To be finished later."),
+        SQLStatement::SQLDelete { .. } => unimplemented!("To be finished later."),
+        _ => unimplemented!("Functionality not implemented."),
+    };
+
+    let selection = match selection {
+        Some(selection) => selection,
+        None => return Ok(None),
+    };
+
+    // A leading `NOT` flips the membership test into `NOT EXISTS`.
+    let (negated, inner) = strip_negation(selection);
+
+    let mut fk_columns = vec![];
+    collect_fk_columns(inner, table, &mut fk_columns);
+    fk_columns.sort_unstable();
+    fk_columns.dedup();
+
+    if fk_columns.is_empty() {
+        return Ok(None);
+    }
+
+    let fk_column_refs: Vec<&str> = fk_columns.iter().map(String::as_str).collect();
+    let fk_refs = match ForeignKeyReference::from_query_columns(conn, table, &fk_column_refs)? {
+        Some(fk_refs) => fk_refs,
+        None => return Ok(None),
+    };
+
+    // A bare dot-path (e.g. `books.id`) is a pure membership test: any existing column is used
+    // only to name the relationship, so no extra filter is ANDed into the subquery. Anything else
+    // (e.g. `books.published = true`) is reassembled into the subquery's WHERE clause below, with
+    // the FK dot-path rewritten to the correlated alias.
+    let inner_predicate = match inner {
+        ASTNode::SQLCompoundIdentifier(_) => None,
+        _ => rewrite_predicate_sql(inner, table),
+    };
+
+    // The membership test correlates on the first resolved reference; any nested references are
+    // folded into the subquery body by `to_exists_subquery`.
+    Ok(Some(fk_refs[0].to_exists_subquery(
+        table,
+        true,
+        negated,
+        inner_predicate.as_deref(),
+    )))
+}
+
+/// Peels any `NOT`/parenthesized wrappers off a predicate, returning whether an odd number of
+/// `NOT`s was seen (the net negation) alongside the innermost node.
+fn strip_negation(node: &ASTNode) -> (bool, &ASTNode) {
+    match node {
+        ASTNode::SQLNested(inner) => strip_negation(inner),
+        ASTNode::SQLUnary {
+            operator: SQLOperator::Not,
+            expr,
+        } => {
+            let (negated, inner) = strip_negation(expr);
+            (!negated, inner)
+        }
+        other => (false, other),
+    }
+}
+
+/// Whether `node` contains a genuine dot-path FK reference anywhere within it (see
+/// [`collect_fk_columns`]).
+fn contains_fk_identifier(node: &ASTNode, table: &str) -> bool {
+    let mut found = vec![];
+    collect_fk_columns(node, table, &mut found);
+    !found.is_empty()
+}
+
+/// Reassembles a predicate back into SQL text for use inside a correlated `EXISTS` subquery,
+/// rewriting any FK dot-path compound identifier (e.g. `books.published`) to the corresponding
+/// subquery alias (e.g. `books__publisher.name` when nested), using the same `__`-joined alias
+/// scheme as [`ForeignKeyReference::to_exists_subquery`] and
+/// [`ForeignKeyReference::build_inner_joins`]. Subtrees with no FK reference are passed through via
+/// the parser's own SQL rendering. Returns `None` for expression shapes this rewrite does not
+/// (yet) understand (e.g. an FK column inside a function call or `IN` list), signaling the caller
+/// to drop the extra filter rather than risk emitting a predicate with an unresolved alias.
+fn rewrite_predicate_sql(node: &ASTNode, table: &str) -> Option<String> {
+    if !contains_fk_identifier(node, table) {
+        return Some(node.to_string());
+    }
+
+    match node {
+        ASTNode::SQLCompoundIdentifier(parts) if parts.len() > 1 => {
+            // safe: the `parts.len() > 1` guard guarantees at least two segments
+            let (final_column, referring_path) = parts.split_last().unwrap();
+            Some(format!("{}.{}", referring_path.join("__"), final_column))
+        }
+        ASTNode::SQLNested(expr) => rewrite_predicate_sql(expr, table).map(|e| format!("({})", e)),
+        ASTNode::SQLUnary { operator, expr } => {
+            rewrite_predicate_sql(expr, table).map(|e| format!("{} {}", operator, e))
+        }
+        ASTNode::SQLIsNull(expr) => {
+            rewrite_predicate_sql(expr, table).map(|e| format!("{} IS NULL", e))
+        }
+        ASTNode::SQLIsNotNull(expr) => {
+            rewrite_predicate_sql(expr, table).map(|e| format!("{} IS NOT NULL", e))
+        }
+        ASTNode::SQLBinaryExpr { left, op, right } => {
+            let left = rewrite_predicate_sql(left, table)?;
+            let right = rewrite_predicate_sql(right, table)?;
+            Some(format!("{} {} {}", left, op, right))
+        }
+        _ => None,
+    }
+}
+
+/// Recursively walks an expression node, collecting every compound (dot-separated) identifier that
+/// is a genuine dot-path FK traversal (e.g. `another_foreign_key.nested_fk.some_int`) as a foreign
+/// key column reference. A compound identifier qualified with the base `table` itself (e.g.
+/// `a_table.column`) is an ordinary column reference, not an FK traversal, and is skipped.
+/// Operators and literals are traversed but ignored so that only the column identifiers are
+/// gathered; the caller reassembles the clause after alias rewriting.
+fn collect_fk_columns(node: &ASTNode, table: &str, columns: &mut Vec<String>) {
+    match node {
+        ASTNode::SQLCompoundIdentifier(parts) => {
+            if parts.len() > 1 && parts[0] != table {
+                columns.push(parts.join("."));
+            }
+        }
+        ASTNode::SQLIsNull(expr) | ASTNode::SQLIsNotNull(expr) | ASTNode::SQLNested(expr) => {
+            collect_fk_columns(expr, table, columns);
+        }
+        ASTNode::SQLUnary { expr, .. } | ASTNode::SQLCast { expr, .. } => {
+            collect_fk_columns(expr, table, columns);
+        }
+        ASTNode::SQLBinaryExpr { left, right, .. } => {
+            collect_fk_columns(left, table, columns);
+            collect_fk_columns(right, table, columns);
+        }
+        ASTNode::SQLInList { expr, list, .. } => {
+            collect_fk_columns(expr, table, columns);
+            for item in list {
+                collect_fk_columns(item, table, columns);
+            }
+        }
+        ASTNode::SQLBetween {
+            expr, low, high, ..
+        } => {
+            collect_fk_columns(expr, table, columns);
+            collect_fk_columns(low, table, columns);
+            collect_fk_columns(high, table, columns);
+        }
+        ASTNode::SQLFunction { args, .. } => {
+            for arg in args {
+                collect_fk_columns(arg, table, columns);
+            }
+        }
+        _ => {}
+    }
 }
 
 /// Represents a single foreign key, usually generated by a queried column using dot-syntax.
@@ -37,6 +243,13 @@ pub struct ForeignKeyReference {
     /// The column of the table being referred by the foreign key.
     pub table_column_referred: String,
 
+    /// Whether this is a reverse (one-to-many) relationship. When `false` (the default), this is an
+    /// outgoing many-to-one foreign key (this table → `table_referred`) and is emitted as an
+    /// `INNER JOIN`. When `true`, `table_referred` is a table whose foreign key (`table_column_referred`)
+    /// points back at this table's `referring_column`; such references are aggregated with
+    /// `json_agg` grouped by the parent key rather than row-multiplying joins.
+    pub is_reverse: bool,
+
     /// Any child foreign key columns that are part of the original_ref string.
     pub nested_fks: Option<Vec<ForeignKeyReference>>,
 }
@@ -156,7 +369,7 @@ impl ForeignKeyReference {
         for col in fk_columns.iter() {
             if let Some(dot_index) = col.find('.') {
                 if let (Some(parent_col_name), Some(child_column)) =
-                    (col.get(0..dot_index), col.get(dot_index..))
+                    (col.get(0..dot_index), col.get(dot_index + 1..))
                 {
                     if !fk_columns_grouped.contains_key(parent_col_name) {
                         fk_columns_grouped.insert(parent_col_name, (vec![child_column], vec![col]));
@@ -222,6 +435,7 @@ impl ForeignKeyReference {
                             table_column_referred: stat
                                 .foreign_key_columns
                                 .unwrap_or_else(String::new),
+                            is_reverse: false,
                             nested_fks: Some(fk_result_vec),
                             original_refs,
                         }));
@@ -233,22 +447,230 @@ impl ForeignKeyReference {
                     referring_column: stat.column_name,
                     table_referred: foreign_key_table,
                     table_column_referred: stat.foreign_key_columns.unwrap_or_else(String::new),
+                    is_reverse: false,
                     nested_fks: None,
                     original_refs,
                 }))
             })
             .collect();
 
-        Ok(Some(filtered_stats_result?))
+        let mut fk_refs = filtered_stats_result?;
+
+        // Resolve any remaining parent columns as reverse (one-to-many) relationships: a dot-path
+        // like `books.title` where `books` is a table whose foreign key targets this table.
+        let matched_parents: Vec<&str> = fk_refs
+            .iter()
+            .map(|fk_ref| fk_ref.referring_column.as_str())
+            .collect();
+        let reverse_stats = get_reverse_column_stats(conn, table)?;
+        for (&parent_col, (child_columns, original_refs)) in fk_columns_grouped.iter() {
+            if matched_parents.contains(&parent_col) {
+                continue;
+            }
+
+            // `parent_col` is the name of a table that references this one.
+            if let Some(reverse_stat) = reverse_stats
+                .iter()
+                .find(|stat| stat.table_name == parent_col)
+            {
+                // A reverse (one-to-many) embed is aggregated into a single `json_agg` of
+                // `parent_col`'s own columns; it cannot also walk a further forward FK on
+                // `parent_col` (e.g. `books.publisher.name`), since that would require joining a
+                // third table into the aggregate. Reject rather than silently emit SQL that
+                // references a column that does not exist on `parent_col`.
+                if let Some(&nested_col) = child_columns.iter().find(|col| col.contains('.')) {
+                    return Err(ApiError::generate_error(
+                        "NESTED_FK_IN_REVERSE_EMBED_NOT_SUPPORTED",
+                        format!(
+                            "\"{}.{}\" is not supported: a reverse one-to-many embed (\"{}\") cannot also traverse a nested foreign key.",
+                            parent_col, nested_col, parent_col,
+                        ),
+                    ));
+                }
+
+                fk_refs.push(ForeignKeyReference {
+                    // the parent-side key that the child foreign key points at
+                    referring_column: reverse_stat.foreign_key_column.clone(),
+                    table_referred: parent_col.to_string(),
+                    // the child's foreign key column that points back at this table
+                    table_column_referred: reverse_stat.column_name.clone(),
+                    is_reverse: true,
+                    nested_fks: None,
+                    original_refs: original_refs.iter().map(|col| col.to_string()).collect(),
+                });
+            }
+        }
+
+        if fk_refs.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(fk_refs))
+        }
     }
 
+    /// Given a table name, the originally-requested columns, and the resolved foreign key
+    /// references, construct (1) the list of qualified/aliased SELECT column expressions and (2) a
+    /// single `INNER JOIN` string to be used in a query.
+    ///
+    /// The FK tree is walked depth-first. Each node gets a deterministic alias derived from its
+    /// join path (the chain of referring columns joined by `__`), so sibling foreign keys that
+    /// point at the same table do not collide. Each dot-syntax column (e.g.
+    /// `another_foreign_key.nested_fk.some_str`) is rewritten to `<leaf_alias>.<final_column>`,
+    /// except columns of a reverse (one-to-many) reference: those emit no JOIN and are gathered into
+    /// a single `json_agg` column per reference instead.
+    pub fn fk_reference_arr_to_sql(
+        table: &str,
+        columns: &[&str],
+        fk_refs: &[Self],
+    ) -> (Vec<String>, String) {
+        let mut joins = vec![];
+        let mut select_columns = vec![];
+        // Original refs handled by a reverse (one-to-many) relationship are aggregated into a
+        // single json_agg column and must be skipped by the per-column rewrite below.
+        let mut reverse_refs: Vec<&str> = vec![];
+
+        for fk_ref in fk_refs {
+            if fk_ref.is_reverse {
+                select_columns.push(fk_ref.reverse_json_agg_select(table));
+                reverse_refs.extend(fk_ref.original_refs.iter().map(String::as_str));
+            } else {
+                fk_ref.build_inner_joins(table, true, &mut joins);
+            }
+        }
+
+        for col in columns {
+            if reverse_refs.contains(col) {
+                // covered by the json_agg column emitted above
+                continue;
+            }
 
-    // /// Given a table name and list of foreign key references, construct the column and `INNER JOIN` SQL strings to be used in a query.
-    // pub fn fk_reference_arr_to_sql(
-    //     table: &str,
-    //     columns: &[&str],
-    //     fk_refs: &[Self],
-    // ) -> (Vec<String>, String) {
-    //     (vec![], "".to_string())
-    // }
+            if col.contains('.') {
+                let segments: Vec<&str> = col.split('.').collect();
+                // safe: `contains('.')` guarantees at least two segments
+                let (final_column, referring_path) = segments.split_last().unwrap();
+                let leaf_alias = referring_path.join("__");
+                select_columns.push(format!("{}.{} AS \"{}\"", leaf_alias, final_column, col));
+            } else {
+                select_columns.push(format!("{}.{}", table, col));
+            }
+        }
+
+        (select_columns, joins.join(" "))
+    }
+
+    /// Builds the aggregated SELECT column expression for a reverse (one-to-many) relationship.
+    /// The child rows matching this parent key are gathered into a JSON array with `json_agg`,
+    /// correlated on the child foreign key column, so the parent row is not multiplied by a join.
+    fn reverse_json_agg_select(&self, parent_table: &str) -> String {
+        let object_pairs = self
+            .original_refs
+            .iter()
+            .filter_map(|original_ref| {
+                original_ref
+                    .rsplit('.')
+                    .next()
+                    .map(|column| format!("'{0}', {1}.{0}", column, self.table_referred))
+            })
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        format!(
+            "(SELECT COALESCE(json_agg(json_build_object({pairs})), '[]'::json) FROM {child} WHERE {child}.{child_fk} = {parent}.{parent_key}) AS \"{child}\"",
+            pairs = object_pairs,
+            child = self.table_referred,
+            child_fk = self.table_column_referred,
+            parent = parent_table,
+            parent_key = self.referring_column,
+        )
+    }
+
+    /// Translates this foreign key reference into a correlated `EXISTS` / `NOT EXISTS` subquery,
+    /// used to filter a table by the presence or absence of related rows (e.g. "all authors that
+    /// have no published books"). When `negated` is `true`, `NOT EXISTS` is emitted; otherwise
+    /// `EXISTS`.
+    ///
+    /// The subquery's correlation condition equates the parent key to the child foreign key
+    /// column, and `inner_predicate` (if any) is ANDed into the subquery body. Unlike
+    /// [`ForeignKeyReference::fk_reference_arr_to_sql`], no JOIN is emitted and the subquery's
+    /// alias/columns are scoped to the subquery only, so they never leak into the outer SELECT
+    /// column list or JOINs.
+    ///
+    /// `parent_alias` is the alias of the referencing table (the base table name when `is_base`).
+    /// As with [`ForeignKeyReference::build_inner_joins`], the alias for this node is derived from
+    /// `parent_alias` joined by `__` at every nesting level below the base, so a nested FK never
+    /// reuses a bare column name that a sibling or ancestor subquery also uses as its alias. The
+    /// segment named at each level is whatever [`rewrite_predicate_sql`] treats as that level of
+    /// the dot-path: the FK column name for a forward reference, or the referenced table name for
+    /// a reverse one (since a reverse dot-path like `books.published` names the relationship by
+    /// `books`, not by the parent key it is aliased by).
+    pub fn to_exists_subquery(
+        &self,
+        parent_alias: &str,
+        is_base: bool,
+        negated: bool,
+        inner_predicate: Option<&str>,
+    ) -> String {
+        let alias_segment = if self.is_reverse {
+            &self.table_referred
+        } else {
+            &self.referring_column
+        };
+        let alias = if is_base {
+            alias_segment.clone()
+        } else {
+            format!("{}__{}", parent_alias, alias_segment)
+        };
+        let keyword = if negated { "NOT EXISTS" } else { "EXISTS" };
+
+        let mut where_parts = vec![format!(
+            "{}.{} = {}.{}",
+            parent_alias, self.referring_column, alias, self.table_column_referred,
+        )];
+
+        // Nested foreign keys become nested (positive) EXISTS subqueries inside this body, keeping
+        // their columns scoped to the inner subquery.
+        if let Some(nested_fks) = &self.nested_fks {
+            for nested_fk in nested_fks {
+                where_parts.push(nested_fk.to_exists_subquery(&alias, false, false, None));
+            }
+        }
+
+        if let Some(predicate) = inner_predicate {
+            where_parts.push(predicate.to_string());
+        }
+
+        format!(
+            "{} (SELECT 1 FROM {} AS {} WHERE {})",
+            keyword,
+            self.table_referred,
+            alias,
+            where_parts.join(" AND "),
+        )
+    }
+
+    /// Recursively appends the `INNER JOIN` clause for this node and its nested foreign keys.
+    /// `parent_alias` is the alias of the referencing table (the base table name when `is_base`).
+    fn build_inner_joins(&self, parent_alias: &str, is_base: bool, joins: &mut Vec<String>) {
+        let alias = if is_base {
+            self.referring_column.clone()
+        } else {
+            format!("{}__{}", parent_alias, self.referring_column)
+        };
+
+        joins.push(format!(
+            "INNER JOIN {} AS {} ON {}.{} = {}.{}",
+            self.table_referred,
+            alias,
+            parent_alias,
+            self.referring_column,
+            alias,
+            self.table_column_referred,
+        ));
+
+        if let Some(nested_fks) = &self.nested_fks {
+            for nested_fk in nested_fks {
+                nested_fk.build_inner_joins(&alias, false, joins);
+            }
+        }
+    }
 }
\ No newline at end of file
@@ -1,303 +1,440 @@
-// use super::{
-//     postgres_types::{convert_row_fields, ColumnTypeValue, RowFields},
-//     query_types::{Query, QueryParams, QueryParamsInsert, QueryResult},
-//     table_stats::select_column_stats,
-// };
-// use crate::db::PgConnection;
-// use crate::errors::ApiError;
-// use futures::future;
-// use tokio_postgres::TransactionBuilder;
-// use tokio_postgres::types::ToSql;
-// use serde_json::{Map, Value};
-// use std::collections::HashMap;
-
-// static INSERT_ROWS_BATCH_COUNT: usize = 2;
-
-// enum InsertResult {
-//     Rows(Vec<RowFields>),
-//     NumRowsAffected(u64),
-// }
-
-// /// Runs an INSERT INTO <table> query
-// pub fn insert_into_table(conn: &PgConnection, query: Query) -> Result<QueryResult, ApiError> {
-//     // extract query data
-//     let mut query_params: QueryParamsInsert;
-//     match query.params {
-//         QueryParams::Insert(insert_params) => query_params = insert_params,
-//         _ => unreachable!("insert_into_table() should not be called without Insert parameter."),
-//     };
-
-//     // serde_json::Values can't automatically convert to non-JSON/JSONB columns.
-//     // Therefore, get column types of table so we know what types into which the json values are converted.
-//     // apparently rust_postgres already does this in the background, would be nice if there was a way to hook into existing functionality...
-//     let mut column_types: HashMap<String, String> = HashMap::new();
-//     for stat in select_column_stats(conn, &query_params.table)?.into_iter() {
-//         column_types.insert(stat.column_name, stat.column_type);
-//     }
-
-//     let num_rows = query_params.rows.len();
-//     let mut total_num_rows_affected = 0;
-//     let mut total_rows_returned = vec![];
-
-//     // let transaction = conn.client.build_transaction().build()
-
-//     // currently, we use 1 database transaction to wrap all inserts
-//     // using tokio-postgres transaction builder, we need to:
-//     // 1. lazily build all the insert executions in a lazy future (so they don't run right when we call them)
-//     // 2. wrap them in a transaction builder so it runs all at once inside a transaction
-
-//     if num_rows >= INSERT_ROWS_BATCH_COUNT {
-//         // batch inserts into groups of 100 (see https://www.depesz.com/2007/07/05/how-to-insert-data-to-database-as-fast-as-possible/)
-
-//         let batch_insert = future::lazy(|| {});
-
-//         let mut batch_rows = vec![];
-//         for (i, row) in query_params.rows.iter().enumerate() {
-//             batch_rows.push(row);
-
-//             if (i + 1) % INSERT_ROWS_BATCH_COUNT == 0 || i == num_rows - 1 {
-//                 // do batch inserts on pushed rows
-//                 match execute_insert(&conn, &query_params, &column_types, &batch_rows)? {
-//                     InsertResult::NumRowsAffected(num_rows_affected) => {
-//                         total_num_rows_affected += num_rows_affected
-//                     }
-//                     InsertResult::Rows(rows) => {
-//                         total_rows_returned.extend(rows);
-//                     }
-//                 };
-
-//                 // reset batch
-//                 batch_rows.truncate(0);
-//             }
-//         }
-//     } else {
-//         // insert all rows
-//         match execute_insert(
-//             &conn,
-//             &query_params,
-//             &column_types,
-//             &query_params
-//                 .rows
-//                 .iter()
-//                 .collect::<Vec<&Map<String, Value>>>(),
-//         )? {
-//             InsertResult::NumRowsAffected(num_rows_affected) => {
-//                 total_num_rows_affected += num_rows_affected
-//             }
-//             InsertResult::Rows(rows) => {
-//                 total_rows_returned.extend(rows);
-//             }
-//         };
-//     }
-
-//     transaction.commit()?;
-
-//     if query_params.returning_columns.is_some() {
-//         Ok(QueryResult::QueryTableResult(total_rows_returned))
-//     } else {
-//         Ok(QueryResult::from_num_rows_affected(total_num_rows_affected))
-//     }
-// }
-
-// /// Runs the actual setting up + execution of the INSERT query
-// fn execute_insert<'a>(
-//     conn: &PgConnection,
-//     query_params: &QueryParamsInsert,
-//     column_types: &HashMap<String, String>,
-//     rows: &'a [&'a Map<String, Value>],
-// ) -> Result<InsertResult, ApiError> {
-//     let mut is_return_rows = false;
-
-//     // parse out the columns that have values to assign
-//     let columns = get_all_columns_to_insert(rows);
-//     let (values_params_str, column_values) = get_insert_params(rows, &columns, column_types)?;
-
-//     // generate the ON CONFLICT string
-//     let conflict_clause = match generate_conflict_str(query_params, &columns) {
-//         Some(conflict_str) => conflict_str,
-//         None => "".to_string(),
-//     };
-
-//     // generate the RETURNING string
-//     let returning_clause = match generate_returning_clause(query_params) {
-//         Some(returning_str) => {
-//             is_return_rows = true;
-//             returning_str
-//         }
-//         None => "".to_string(),
-//     };
-
-//     // create initial prepared statement
-//     let insert_query_str = [
-//         "INSERT INTO ",
-//         &query_params.table,
-//         &[" (", &columns.join(", "), ")"].join(""),
-//         " VALUES ",
-//         &values_params_str,
-//         &conflict_clause,
-//         &returning_clause,
-//     ]
-//     .join("");
-
-//     let prep_statement = conn.client.prepare(&insert_query_str)?;
-
-//     // convert the column values into the actual values we will use for the INSERT statement execution
-//     let mut prep_values: Vec<&ToSql> = vec![];
-//     for column_value in column_values.iter() {
-//         match column_value {
-//             ColumnTypeValue::BigInt(col_val) => prep_values.push(col_val),
-//             ColumnTypeValue::Bool(col_val) => prep_values.push(col_val),
-//             ColumnTypeValue::ByteA(col_val) => prep_values.push(col_val),
-//             ColumnTypeValue::Char(col_val) => prep_values.push(col_val),
-//             ColumnTypeValue::Citext(col_val) => prep_values.push(col_val),
-//             ColumnTypeValue::Date(col_val) => prep_values.push(col_val),
-//             ColumnTypeValue::Decimal(col_val) => prep_values.push(col_val),
-//             ColumnTypeValue::Float8(col_val) => prep_values.push(col_val),
-//             ColumnTypeValue::HStore(col_val) => prep_values.push(col_val),
-//             ColumnTypeValue::Int(col_val) => prep_values.push(col_val),
-//             ColumnTypeValue::Json(col_val) => prep_values.push(col_val),
-//             ColumnTypeValue::JsonB(col_val) => prep_values.push(col_val),
-//             ColumnTypeValue::MacAddr(col_val) => prep_values.push(col_val),
-//             ColumnTypeValue::Name(col_val) => prep_values.push(col_val),
-//             ColumnTypeValue::Oid(col_val) => prep_values.push(col_val),
-//             ColumnTypeValue::Real(col_val) => prep_values.push(col_val),
-//             ColumnTypeValue::SmallInt(col_val) => prep_values.push(col_val),
-//             ColumnTypeValue::Text(col_val) => prep_values.push(col_val),
-//             ColumnTypeValue::Time(col_val) => prep_values.push(col_val),
-//             ColumnTypeValue::Timestamp(col_val) => prep_values.push(col_val),
-//             ColumnTypeValue::TimestampTz(col_val) => prep_values.push(col_val),
-//             ColumnTypeValue::Uuid(col_val) => prep_values.push(col_val),
-//             ColumnTypeValue::VarChar(col_val) => prep_values.push(col_val),
-//         };
-//     }
-
-//     // execute sql & return results
-//     if is_return_rows {
-//         let results: Vec<RowFields> = prep_statement
-//             .query(&prep_values)?
-//             .iter()
-//             .map(|row| convert_row_fields(&row))
-//             .collect::<Result<Vec<RowFields>, ApiError>>()?;
-//         Ok(InsertResult::Rows(results))
-//     } else {
-//         let results = prep_statement.execute(&prep_values)?;
-//         Ok(InsertResult::NumRowsAffected(results))
-//     }
-// }
-
-// /// Generates the ON CONFLICT clause. If conflict action is "nothing", then "DO NOTHING" is returned. If conflict action is "update", then sets all columns that aren't conflict target columns to the excluded row's column value.
-// fn generate_conflict_str(query_params: &QueryParamsInsert, columns: &[&str]) -> Option<String> {
-//     if let (Some(conflict_action_str), Some(conflict_target_vec)) =
-//         (&query_params.conflict_action, &query_params.conflict_target)
-//     {
-//         // filter out any conflict target columns and convert the remaining columns into "SET <col> = EXCLUDED.<col>" clauses
-//         let expanded_conflict_action = if conflict_action_str == "update" {
-//             [
-//                 "DO UPDATE SET ",
-//                 &columns
-//                     .iter()
-//                     .filter_map(|col| {
-//                         match conflict_target_vec
-//                             .iter()
-//                             .position(|conflict_target| conflict_target == *col)
-//                         {
-//                             Some(_) => None,
-//                             None => Some([*col, "=", "EXCLUDED.", *col].join("")),
-//                         }
-//                     })
-//                     .collect::<Vec<String>>()
-//                     .join(", "),
-//             ]
-//             .join("")
-//         } else {
-//             "DO NOTHING".to_string()
-//         };
-
-//         return Some(
-//             [
-//                 " ON CONFLICT (",
-//                 &conflict_target_vec.join(", "),
-//                 ") ",
-//                 &expanded_conflict_action,
-//             ]
-//             .join(""),
-//         );
-//     }
-
-//     None
-// }
-
-// fn generate_returning_clause(query_params: &QueryParamsInsert) -> Option<String> {
-//     if let Some(returning_columns) = &query_params.returning_columns {
-//         return Some([" RETURNING ", &returning_columns.join(", ")].join(""));
-//     }
-
-//     None
-// }
-
-// /// Searches all rows being inserted and returns a vector containing all of the column names
-// fn get_all_columns_to_insert<'a>(rows: &'a [&'a Map<String, Value>]) -> Vec<&'a str> {
-//     // parse out the columns that have values to assign
-//     let mut columns: Vec<&str> = vec![];
-//     for row in rows.iter() {
-//         for column in row.keys() {
-//             if columns.iter().position(|&c| c == column).is_none() {
-//                 columns.push(column);
-//             }
-//         }
-//     }
-//     columns
-// }
-
-// /// Returns a Result containing the tuple that contains (the VALUES parameter string, the array of parameter values)
-// fn get_insert_params(
-//     rows: &[&Map<String, Value>],
-//     columns: &[&str],
-//     column_types: &HashMap<String, String>,
-// ) -> Result<(String, Vec<ColumnTypeValue>), ApiError> {
-//     let mut prep_column_number = 1;
-//     let mut row_strs = vec![];
-
-//     // generate the array of json-converted-to-rust_postgres values to insert.
-//     let nested_column_values_result: Result<Vec<Vec<ColumnTypeValue>>, ApiError> = rows
-//         .iter()
-//         .map(|row| -> Result<Vec<ColumnTypeValue>, ApiError> {
-//             // row_str_arr is used for the prepared statement parameter string
-//             let mut row_str_arr: Vec<String> = vec![];
-//             let mut column_values: Vec<ColumnTypeValue> = vec![];
-
-//             for column in columns.iter() {
-//                 // if the "row" json object has a value for column, then use the rust-converted value, otherwise use the column’s DEFAULT value
-//                 match row.get(*column) {
-//                     Some(val) => {
-//                         let prep_column_number_str =
-//                             ["$", &prep_column_number.to_string()].join("");
-//                         row_str_arr.push(prep_column_number_str);
-//                         prep_column_number += 1;
-
-//                         let column_type = &column_types[*column];
-//                         match ColumnTypeValue::from_json(column_type, val) {
-//                             Ok(column_type_value) => {
-//                                 column_values.push(column_type_value);
-//                             }
-//                             Err(e) => return Err(e),
-//                         }
-//                     }
-//                     None => {
-//                         row_str_arr.push("DEFAULT".to_string());
-//                     }
-//                 };
-//             }
-
-//             row_strs.push(format!("({})", row_str_arr.join(", ")));
-//             Ok(column_values)
-//         })
-//         .collect();
-
-//     let values_str = row_strs.join(", ");
-
-//     let nested_column_values = nested_column_values_result?;
-//     let column_values: Vec<ColumnTypeValue> = nested_column_values.into_iter().flatten().collect();
-
-//     Ok((values_str, column_values))
-// }
+use super::{
+    postgres_types::{convert_row_fields, ColumnTypeValue, RowFields},
+    query_types::{Query, QueryParams, QueryParamsInsert, QueryResult},
+    table_stats::select_column_stats,
+};
+use crate::db::PgConnection;
+use crate::errors::ApiError;
+use postgres::binary_copy::BinaryCopyInWriter;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use tokio_postgres::types::{ToSql, Type};
+
+/// Postgres refuses a statement with more than this many bind parameters.
+static MAX_BIND_PARAMS: usize = 65535;
+
+/// Upper bound on the serialized length (in bytes) of a single INSERT statement's `VALUES` list, as
+/// estimated by `estimate_row_bytes`. Chunks are split so that no batch's estimate exceeds this
+/// (unless a single row alone does), independent of the bind-parameter limit. The bound is fixed;
+/// neither the request nor the crate config can change it.
+static DEFAULT_MAX_QUERY_BYTES: usize = 200_000;
+
+enum InsertResult {
+    Rows(Vec<RowFields>),
+    NumRowsAffected(u64),
+}
+
+/// Runs an INSERT INTO <table> query. On the INSERT path, large row arrays are split into batches
+/// that respect both Postgres's bind-parameter limit and `DEFAULT_MAX_QUERY_BYTES`, and all batches
+/// run inside a single transaction so the insert is atomic. On the COPY fast-path every row is
+/// streamed through one `COPY` inside that transaction, without batching.
+pub fn insert_into_table(conn: &PgConnection, query: Query) -> Result<QueryResult, ApiError> {
+    // extract query data
+    let query_params: QueryParamsInsert;
+    match query.params {
+        QueryParams::Insert(insert_params) => query_params = insert_params,
+        _ => unreachable!("insert_into_table() should not be called without Insert parameter."),
+    };
+
+    // serde_json::Values can't automatically convert to non-JSON/JSONB columns.
+    // Therefore, get column types of table so we know what types into which the json values are converted.
+    let mut column_types: HashMap<String, String> = HashMap::new();
+    for stat in select_column_stats(conn, &query_params.table)?.into_iter() {
+        column_types.insert(stat.column_name, stat.column_type);
+    }
+
+    let columns = get_all_columns_to_insert(
+        &query_params
+            .rows
+            .iter()
+            .collect::<Vec<&Map<String, Value>>>(),
+    );
+
+    let mut total_num_rows_affected = 0;
+    let mut total_rows_returned = vec![];
+
+    // wrap all work in a single transaction so the whole insert commits atomically or rolls back
+    // on any error.
+    let mut transaction = conn.client.transaction()?;
+
+    // COPY fast-path: when there is no ON CONFLICT handling and no RETURNING clause, streaming the
+    // rows through the binary `COPY ... FROM STDIN` protocol is dramatically faster than multi-row
+    // INSERTs. Anything that needs conflict handling or returned rows falls back to the prepared
+    // INSERT path below.
+    let use_copy = query_params.conflict_action.is_none()
+        && query_params.returning_columns.is_none()
+        && rows_have_all_columns(&query_params.rows, &columns);
+
+    if use_copy {
+        match execute_copy_insert(&mut transaction, &query_params, &column_types, &columns) {
+            Ok(num_rows_affected) => total_num_rows_affected += num_rows_affected,
+            Err(e) => {
+                transaction.rollback()?;
+                return Err(e);
+            }
+        }
+    } else {
+        let batches = split_rows_into_batches(
+            &query_params.rows,
+            columns.len(),
+            query_params.batch_size,
+            DEFAULT_MAX_QUERY_BYTES,
+        );
+
+        for batch_rows in &batches {
+            match execute_insert(&mut transaction, &query_params, &column_types, batch_rows) {
+                Ok(InsertResult::NumRowsAffected(num_rows_affected)) => {
+                    total_num_rows_affected += num_rows_affected
+                }
+                Ok(InsertResult::Rows(rows)) => total_rows_returned.extend(rows),
+                Err(e) => {
+                    transaction.rollback()?;
+                    return Err(e);
+                }
+            };
+        }
+    }
+
+    transaction.commit()?;
+
+    if query_params.returning_columns.is_some() {
+        Ok(QueryResult::QueryTableResult(total_rows_returned))
+    } else {
+        Ok(QueryResult::from_num_rows_affected(total_num_rows_affected))
+    }
+}
+
+/// Streams every row into the table via the binary `COPY ... FROM STDIN` protocol. Used only when
+/// no ON CONFLICT or RETURNING handling is needed. Shares the per-column `ColumnTypeValue`
+/// conversion with the INSERT path.
+fn execute_copy_insert(
+    transaction: &mut postgres::Transaction,
+    query_params: &QueryParamsInsert,
+    column_types: &HashMap<String, String>,
+    columns: &[&str],
+) -> Result<u64, ApiError> {
+    let copy_statement = [
+        "COPY ",
+        &query_params.table,
+        &[" (", &columns.join(", "), ")"].join(""),
+        " FROM STDIN BINARY",
+    ]
+    .join("");
+
+    let pg_types: Vec<Type> = columns
+        .iter()
+        .map(|column| ColumnTypeValue::pg_type(&column_types[*column]))
+        .collect::<Result<Vec<Type>, ApiError>>()?;
+
+    let sink = transaction.copy_in(&copy_statement)?;
+    let mut writer = BinaryCopyInWriter::new(sink, &pg_types);
+
+    let mut num_rows = 0;
+    for row in &query_params.rows {
+        let column_values = row_to_column_values(row, columns, column_types)?;
+        let to_sql_values = column_values_to_to_sql(&column_values);
+        writer.write(&to_sql_values)?;
+        num_rows += 1;
+    }
+    writer.finish()?;
+
+    Ok(num_rows)
+}
+
+/// Splits `rows` into batches such that no batch exceeds the bind-parameter limit
+/// (`floor(MAX_BIND_PARAMS / params_per_row)` rows) and no batch's estimated serialized statement
+/// length exceeds `max_query_bytes`. A single oversized row is still emitted on its own so the
+/// insert always makes progress.
+fn split_rows_into_batches<'a>(
+    rows: &'a [Map<String, Value>],
+    params_per_row: usize,
+    batch_size: Option<usize>,
+    max_query_bytes: usize,
+) -> Vec<Vec<&'a Map<String, Value>>> {
+    // `params_per_row` is 0 only when there are no columns to insert; keep every row in one batch.
+    let max_rows_by_params = if params_per_row == 0 {
+        rows.len().max(1)
+    } else {
+        (MAX_BIND_PARAMS / params_per_row).max(1)
+    };
+
+    // An explicit per-request batch size further caps the rows-per-batch, but can never exceed the
+    // bind-parameter limit.
+    let max_rows_by_params = match batch_size {
+        Some(batch_size) if batch_size > 0 => max_rows_by_params.min(batch_size),
+        _ => max_rows_by_params,
+    };
+
+    let mut batches = vec![];
+    let mut current: Vec<&Map<String, Value>> = vec![];
+    let mut current_bytes = 0;
+
+    for row in rows.iter() {
+        let row_bytes = estimate_row_bytes(row);
+        let would_overflow_bytes =
+            !current.is_empty() && current_bytes + row_bytes > max_query_bytes;
+        let would_overflow_params = current.len() >= max_rows_by_params;
+
+        if would_overflow_bytes || would_overflow_params {
+            batches.push(std::mem::replace(&mut current, vec![]));
+            current_bytes = 0;
+        }
+
+        current.push(row);
+        current_bytes += row_bytes;
+    }
+
+    if !current.is_empty() {
+        batches.push(current);
+    }
+
+    batches
+}
+
+/// Rough estimate of the serialized length a row's `VALUES` tuple contributes to the generated
+/// statement. Values are emitted as bind placeholders (`$N`), not literals, so the statement grows
+/// by the placeholder token width rather than the payload size. `$` + up to five digits (the
+/// bind-parameter ceiling is 65535) + a `, ` separator bounds each placeholder at eight bytes; the
+/// enclosing `()` of the tuple adds two more.
+fn estimate_row_bytes(row: &Map<String, Value>) -> usize {
+    const PLACEHOLDER_BYTES: usize = "$65535, ".len();
+    row.len() * PLACEHOLDER_BYTES + 2
+}
+
+/// Runs the actual setting up + execution of the INSERT query for a single batch of rows.
+fn execute_insert<'a>(
+    transaction: &mut postgres::Transaction,
+    query_params: &QueryParamsInsert,
+    column_types: &HashMap<String, String>,
+    rows: &'a [&'a Map<String, Value>],
+) -> Result<InsertResult, ApiError> {
+    let mut is_return_rows = false;
+
+    // parse out the columns that have values to assign
+    let columns = get_all_columns_to_insert(rows);
+    let (values_params_str, column_values) = get_insert_params(rows, &columns, column_types)?;
+
+    // generate the ON CONFLICT string
+    let conflict_clause = match generate_conflict_str(query_params, &columns) {
+        Some(conflict_str) => conflict_str,
+        None => "".to_string(),
+    };
+
+    // generate the RETURNING string
+    let returning_clause = match generate_returning_clause(query_params) {
+        Some(returning_str) => {
+            is_return_rows = true;
+            returning_str
+        }
+        None => "".to_string(),
+    };
+
+    // create initial prepared statement
+    let insert_query_str = [
+        "INSERT INTO ",
+        &query_params.table,
+        &[" (", &columns.join(", "), ")"].join(""),
+        " VALUES ",
+        &values_params_str,
+        &conflict_clause,
+        &returning_clause,
+    ]
+    .join("");
+
+    // Goes straight to `postgres::Transaction::prepare` rather than through
+    // `rust_postgres_rest::StatementCache`: the cache is built around the pooled, async
+    // `tokio_postgres::Client` that `Config::get_connection` hands out, and this function runs
+    // over the synchronous `postgres::Transaction` from `conn.client.transaction()` instead, so
+    // there is no cache handle reachable here to route through.
+    let prep_statement = transaction.prepare(&insert_query_str)?;
+
+    // convert the column values into the actual values we will use for the INSERT statement execution
+    let prep_values = column_values_to_to_sql(&column_values);
+
+    // execute sql & return results
+    if is_return_rows {
+        let results: Vec<RowFields> = transaction
+            .query(&prep_statement, &prep_values)?
+            .iter()
+            .map(|row| convert_row_fields(&row))
+            .collect::<Result<Vec<RowFields>, ApiError>>()?;
+        Ok(InsertResult::Rows(results))
+    } else {
+        let results = transaction.execute(&prep_statement, &prep_values)?;
+        Ok(InsertResult::NumRowsAffected(results))
+    }
+}
+
+/// Generates the ON CONFLICT clause. If conflict action is "nothing", then "DO NOTHING" is returned. If conflict action is "update", then sets all columns that aren't conflict target columns to the excluded row's column value.
+fn generate_conflict_str(query_params: &QueryParamsInsert, columns: &[&str]) -> Option<String> {
+    if let (Some(conflict_action_str), Some(conflict_target_vec)) =
+        (&query_params.conflict_action, &query_params.conflict_target)
+    {
+        // filter out any conflict target columns and convert the remaining columns into "SET <col> = EXCLUDED.<col>" clauses
+        let expanded_conflict_action = if conflict_action_str == "update" {
+            [
+                "DO UPDATE SET ",
+                &columns
+                    .iter()
+                    .filter_map(|col| {
+                        match conflict_target_vec
+                            .iter()
+                            .position(|conflict_target| conflict_target == *col)
+                        {
+                            Some(_) => None,
+                            None => Some([*col, "=", "EXCLUDED.", *col].join("")),
+                        }
+                    })
+                    .collect::<Vec<String>>()
+                    .join(", "),
+            ]
+            .join("")
+        } else {
+            "DO NOTHING".to_string()
+        };
+
+        return Some(
+            [
+                " ON CONFLICT (",
+                &conflict_target_vec.join(", "),
+                ") ",
+                &expanded_conflict_action,
+            ]
+            .join(""),
+        );
+    }
+
+    None
+}
+
+fn generate_returning_clause(query_params: &QueryParamsInsert) -> Option<String> {
+    if let Some(returning_columns) = &query_params.returning_columns {
+        return Some([" RETURNING ", &returning_columns.join(", ")].join(""));
+    }
+
+    None
+}
+
+/// Searches all rows being inserted and returns a vector containing all of the column names
+fn get_all_columns_to_insert<'a>(rows: &'a [&'a Map<String, Value>]) -> Vec<&'a str> {
+    // parse out the columns that have values to assign
+    let mut columns: Vec<&str> = vec![];
+    for row in rows.iter() {
+        for column in row.keys() {
+            if columns.iter().position(|&c| c == column).is_none() {
+                columns.push(column);
+            }
+        }
+    }
+    columns
+}
+
+/// Returns a Result containing the tuple that contains (the VALUES parameter string, the array of parameter values)
+fn get_insert_params(
+    rows: &[&Map<String, Value>],
+    columns: &[&str],
+    column_types: &HashMap<String, String>,
+) -> Result<(String, Vec<ColumnTypeValue>), ApiError> {
+    let mut prep_column_number = 1;
+    let mut row_strs = vec![];
+
+    // generate the array of json-converted-to-rust_postgres values to insert.
+    let nested_column_values_result: Result<Vec<Vec<ColumnTypeValue>>, ApiError> = rows
+        .iter()
+        .map(|row| -> Result<Vec<ColumnTypeValue>, ApiError> {
+            // row_str_arr is used for the prepared statement parameter string
+            let mut row_str_arr: Vec<String> = vec![];
+            let mut column_values: Vec<ColumnTypeValue> = vec![];
+
+            for column in columns.iter() {
+                // if the "row" json object has a value for column, then use the rust-converted value, otherwise use the column’s DEFAULT value
+                match row.get(*column) {
+                    Some(val) => {
+                        let prep_column_number_str =
+                            ["$", &prep_column_number.to_string()].join("");
+                        row_str_arr.push(prep_column_number_str);
+                        prep_column_number += 1;
+
+                        let column_type = &column_types[*column];
+                        match ColumnTypeValue::from_json(column_type, val) {
+                            Ok(column_type_value) => {
+                                column_values.push(column_type_value);
+                            }
+                            Err(e) => return Err(e),
+                        }
+                    }
+                    None => {
+                        row_str_arr.push("DEFAULT".to_string());
+                    }
+                };
+            }
+
+            row_strs.push(format!("({})", row_str_arr.join(", ")));
+            Ok(column_values)
+        })
+        .collect();
+
+    let values_str = row_strs.join(", ");
+
+    let nested_column_values = nested_column_values_result?;
+    let column_values: Vec<ColumnTypeValue> = nested_column_values.into_iter().flatten().collect();
+
+    Ok((values_str, column_values))
+}
+
+/// Converts every `column` of a single `row` into a `ColumnTypeValue`. Used by the COPY fast-path,
+/// which requires a value for every column (COPY has no per-column DEFAULT).
+fn row_to_column_values(
+    row: &Map<String, Value>,
+    columns: &[&str],
+    column_types: &HashMap<String, String>,
+) -> Result<Vec<ColumnTypeValue>, ApiError> {
+    columns
+        .iter()
+        .map(|column| {
+            let val = &row[*column];
+            ColumnTypeValue::from_json(&column_types[*column], val)
+        })
+        .collect()
+}
+
+/// Shared conversion of the generated `ColumnTypeValue`s into the `ToSql` trait objects used by
+/// both the prepared INSERT and the binary COPY execution paths.
+fn column_values_to_to_sql(column_values: &[ColumnTypeValue]) -> Vec<&ToSql> {
+    let mut prep_values: Vec<&ToSql> = vec![];
+    for column_value in column_values.iter() {
+        match column_value {
+            ColumnTypeValue::BigInt(col_val) => prep_values.push(col_val),
+            ColumnTypeValue::Bool(col_val) => prep_values.push(col_val),
+            ColumnTypeValue::ByteA(col_val) => prep_values.push(col_val),
+            ColumnTypeValue::Char(col_val) => prep_values.push(col_val),
+            ColumnTypeValue::Citext(col_val) => prep_values.push(col_val),
+            ColumnTypeValue::Date(col_val) => prep_values.push(col_val),
+            ColumnTypeValue::Decimal(col_val) => prep_values.push(col_val),
+            ColumnTypeValue::Float8(col_val) => prep_values.push(col_val),
+            ColumnTypeValue::HStore(col_val) => prep_values.push(col_val),
+            ColumnTypeValue::Int(col_val) => prep_values.push(col_val),
+            ColumnTypeValue::Json(col_val) => prep_values.push(col_val),
+            ColumnTypeValue::JsonB(col_val) => prep_values.push(col_val),
+            ColumnTypeValue::MacAddr(col_val) => prep_values.push(col_val),
+            ColumnTypeValue::Name(col_val) => prep_values.push(col_val),
+            ColumnTypeValue::Oid(col_val) => prep_values.push(col_val),
+            ColumnTypeValue::Real(col_val) => prep_values.push(col_val),
+            ColumnTypeValue::SmallInt(col_val) => prep_values.push(col_val),
+            ColumnTypeValue::Text(col_val) => prep_values.push(col_val),
+            ColumnTypeValue::Time(col_val) => prep_values.push(col_val),
+            ColumnTypeValue::Timestamp(col_val) => prep_values.push(col_val),
+            ColumnTypeValue::TimestampTz(col_val) => prep_values.push(col_val),
+            ColumnTypeValue::Uuid(col_val) => prep_values.push(col_val),
+            ColumnTypeValue::VarChar(col_val) => prep_values.push(col_val),
+        };
+    }
+    prep_values
+}
+
+/// Returns `true` when every row supplies a value for every column, a precondition of the COPY
+/// fast-path (which cannot fall back to column DEFAULTs).
+fn rows_have_all_columns(rows: &[Map<String, Value>], columns: &[&str]) -> bool {
+    rows.iter()
+        .all(|row| columns.iter().all(|column| row.contains_key(*column)))
+}
@@ -0,0 +1,267 @@
+use crate::errors::ApiError;
+use futures::sync::mpsc::{unbounded, UnboundedReceiver, UnboundedSender};
+use serde_json::{Map, Value};
+use sqlparser::{
+    dialect::PostgreSqlDialect,
+    sqlast::{ASTNode, SQLOperator, SQLSelectItem, SQLSetExpr, SQLStatement, Value as SqlValue},
+    sqlparser::Parser,
+};
+use std::collections::HashMap;
+
+/// An incremental change event emitted to subscribers as the underlying tables mutate.
+#[derive(Clone, Debug)]
+pub enum ChangeEvent {
+    /// A row that is part of the initial result set or newly enters the predicate.
+    Row(Map<String, Value>),
+    /// An existing row whose (non-key) values changed while remaining in the predicate.
+    Change(Map<String, Value>),
+    /// A row (identified by its primary key value) that left the result set.
+    Remove(Value),
+}
+
+/// A parsed, normalized SELECT subscription. The canonical SQL string is used as the subscription
+/// key so identical queries share a single matcher.
+pub struct Subscription {
+    /// Canonicalized single-statement SELECT SQL; also the subscription key.
+    pub canonical_sql: String,
+    /// The base table the subscription reads from.
+    pub table: String,
+    /// The selected columns (empty when the query is `SELECT *`).
+    pub columns: Vec<String>,
+    /// The parsed `WHERE` predicate, evaluated in-process against changed rows.
+    selection: Option<ASTNode>,
+    /// The primary key column used to key the cached result set.
+    primary_key: String,
+    /// Cached result set, keyed by the primary key value's string form for idempotency.
+    cache: HashMap<String, Map<String, Value>>,
+    /// Channel used to broadcast events to this subscription's subscribers.
+    sender: UnboundedSender<ChangeEvent>,
+}
+
+impl Subscription {
+    /// Parses and normalizes `sql`, returning the subscription plus a receiver of change events.
+    /// Rejects multi-statement and non-SELECT input.
+    pub fn new(
+        sql: &str,
+        primary_key: &str,
+    ) -> Result<(Self, UnboundedReceiver<ChangeEvent>), ApiError> {
+        let dialect = PostgreSqlDialect {};
+        let mut statements = Parser::parse_sql(&dialect, sql.to_string())?;
+
+        if statements.len() != 1 {
+            return Err(ApiError::generate_error(
+                "INVALID_SUBSCRIPTION_SQL",
+                "A subscription must be a single SELECT statement.".to_string(),
+            ));
+        }
+
+        let query = match statements.remove(0) {
+            SQLStatement::SQLSelect(query) => query,
+            _ => {
+                return Err(ApiError::generate_error(
+                    "INVALID_SUBSCRIPTION_SQL",
+                    "Only SELECT statements can be subscribed to.".to_string(),
+                ))
+            }
+        };
+
+        let select = match query.body {
+            SQLSetExpr::Select(select) => select,
+            _ => {
+                return Err(ApiError::generate_error(
+                    "INVALID_SUBSCRIPTION_SQL",
+                    "Only simple SELECT statements can be subscribed to.".to_string(),
+                ))
+            }
+        };
+
+        let table = match select.relation.as_ref() {
+            Some(ASTNode::SQLIdentifier(table)) => table.clone(),
+            Some(ASTNode::SQLCompoundIdentifier(parts)) => parts.join("."),
+            _ => {
+                return Err(ApiError::generate_error(
+                    "INVALID_SUBSCRIPTION_SQL",
+                    "Could not determine the base table of the subscription.".to_string(),
+                ))
+            }
+        };
+
+        let columns = select
+            .projection
+            .iter()
+            .filter_map(|item| match item {
+                SQLSelectItem::UnnamedExpression(ASTNode::SQLIdentifier(col)) => Some(col.clone()),
+                SQLSelectItem::UnnamedExpression(ASTNode::SQLCompoundIdentifier(parts)) => {
+                    Some(parts.join("."))
+                }
+                _ => None,
+            })
+            .collect();
+
+        // A single canonical string makes identical queries share one matcher.
+        let canonical_sql = sql.split_whitespace().collect::<Vec<&str>>().join(" ");
+        let (sender, receiver) = unbounded();
+
+        let subscription = Subscription {
+            canonical_sql,
+            table,
+            columns,
+            selection: select.selection,
+            primary_key: primary_key.to_string(),
+            cache: HashMap::new(),
+            sender,
+        };
+
+        Ok((subscription, receiver))
+    }
+
+    /// Seeds the cache with the initial result set and emits a `Row` event for each row.
+    pub fn seed_initial(&mut self, rows: Vec<Map<String, Value>>) {
+        for row in rows {
+            if let Some(key) = self.row_key(&row) {
+                self.cache.insert(key, row.clone());
+                let _ = self.sender.unbounded_send(ChangeEvent::Row(row));
+            }
+        }
+    }
+
+    /// Applies a single changed row (from a `LISTEN/NOTIFY` payload or a polling pass), evaluating
+    /// the predicate in-process and diffing against the cached result set. An UPDATE that moves a
+    /// row in or out of the predicate emits a `Row`/`Remove` (not an in-place `Change`).
+    pub fn apply_change(&mut self, row: Map<String, Value>) {
+        let key = match self.row_key(&row) {
+            Some(key) => key,
+            // Without a primary key value we cannot key the row idempotently; ignore it.
+            None => return,
+        };
+
+        let matches = self.matches_predicate(&row);
+        let was_present = self.cache.contains_key(&key);
+
+        match (was_present, matches) {
+            // entered the predicate
+            (false, true) => {
+                self.cache.insert(key, row.clone());
+                let _ = self.sender.unbounded_send(ChangeEvent::Row(row));
+            }
+            // left the predicate
+            (true, false) => {
+                self.cache.remove(&key);
+                if let Some(pk_value) = row.get(&self.primary_key) {
+                    let _ = self
+                        .sender
+                        .unbounded_send(ChangeEvent::Remove(pk_value.clone()));
+                }
+            }
+            // stayed in the predicate with (possibly) changed values
+            (true, true) => {
+                self.cache.insert(key, row.clone());
+                let _ = self.sender.unbounded_send(ChangeEvent::Change(row));
+            }
+            // never was and still isn't a member
+            (false, false) => {}
+        }
+    }
+
+    /// Returns the cache key for a row: the string form of its primary key value.
+    fn row_key(&self, row: &Map<String, Value>) -> Option<String> {
+        row.get(&self.primary_key).map(ToString::to_string)
+    }
+
+    /// Evaluates the `WHERE` predicate against a row's column values. A subscription with no
+    /// predicate matches every row.
+    fn matches_predicate(&self, row: &Map<String, Value>) -> bool {
+        match &self.selection {
+            Some(selection) => eval_predicate(selection, row),
+            None => true,
+        }
+    }
+}
+
+/// Recursively evaluates a parsed predicate against a row. Unsupported expressions conservatively
+/// evaluate to `false` so a row is never incorrectly reported as a member.
+fn eval_predicate(node: &ASTNode, row: &Map<String, Value>) -> bool {
+    match node {
+        ASTNode::SQLNested(inner) => eval_predicate(inner, row),
+        ASTNode::SQLBinaryExpr { left, op, right } => match op {
+            SQLOperator::And => eval_predicate(left, row) && eval_predicate(right, row),
+            SQLOperator::Or => eval_predicate(left, row) || eval_predicate(right, row),
+            _ => eval_comparison(left, op, right, row),
+        },
+        // A column absent from the row reads as NULL; any other operand that cannot be resolved
+        // is unsupported and evaluates to `false`.
+        ASTNode::SQLIsNull(expr) => match resolve_value(expr, row) {
+            Some(value) => value.is_null(),
+            None => match **expr {
+                ASTNode::SQLIdentifier(_) | ASTNode::SQLCompoundIdentifier(_) => true,
+                _ => false,
+            },
+        },
+        ASTNode::SQLIsNotNull(expr) => resolve_value(expr, row).map_or(false, |v| !v.is_null()),
+        _ => false,
+    }
+}
+
+/// Evaluates a single comparison (`=`, `!=`, `<`, `<=`, `>`, `>=`) between two operands.
+fn eval_comparison(
+    left: &ASTNode,
+    op: &SQLOperator,
+    right: &ASTNode,
+    row: &Map<String, Value>,
+) -> bool {
+    let (left_value, right_value) = match (resolve_value(left, row), resolve_value(right, row)) {
+        (Some(left_value), Some(right_value)) => (left_value, right_value),
+        _ => return false,
+    };
+
+    match op {
+        SQLOperator::Eq => left_value == right_value,
+        SQLOperator::NotEq => left_value != right_value,
+        SQLOperator::Lt => json_lt(&left_value, &right_value),
+        SQLOperator::LtEq => left_value == right_value || json_lt(&left_value, &right_value),
+        SQLOperator::Gt => json_lt(&right_value, &left_value),
+        SQLOperator::GtEq => left_value == right_value || json_lt(&right_value, &left_value),
+        _ => false,
+    }
+}
+
+/// Resolves an operand to a JSON value: an identifier reads the row's column, a literal parses to
+/// its JSON form.
+fn resolve_value(node: &ASTNode, row: &Map<String, Value>) -> Option<Value> {
+    match node {
+        ASTNode::SQLIdentifier(column) => row.get(column).cloned(),
+        ASTNode::SQLCompoundIdentifier(parts) => {
+            parts.last().and_then(|column| row.get(column).cloned())
+        }
+        ASTNode::SQLValue(value) => Some(sql_value_to_json(value)),
+        _ => None,
+    }
+}
+
+/// Converts a parsed SQL literal into its JSON equivalent by variant, so comparisons against row
+/// values (which are already JSON) use the correct type. Quoted strings become JSON strings,
+/// numeric literals become JSON numbers, booleans become JSON booleans, and `NULL` becomes
+/// `Value::Null`; any other literal form is stringified.
+fn sql_value_to_json(value: &SqlValue) -> Value {
+    match value {
+        SqlValue::SingleQuotedString(string) | SqlValue::NationalStringLiteral(string) => {
+            Value::String(string.clone())
+        }
+        SqlValue::Long(number) => Value::from(*number),
+        SqlValue::Double(number) => Value::from(*number),
+        SqlValue::Boolean(boolean) => Value::Bool(*boolean),
+        SqlValue::Null => Value::Null,
+        other => Value::String(other.to_string()),
+    }
+}
+
+/// Orders two JSON values numerically when both are numbers, lexically when both are strings.
+fn json_lt(left: &Value, right: &Value) -> bool {
+    match (left.as_f64(), right.as_f64()) {
+        (Some(left), Some(right)) => left < right,
+        _ => match (left.as_str(), right.as_str()) {
+            (Some(left), Some(right)) => left < right,
+            _ => false,
+        },
+    }
+}
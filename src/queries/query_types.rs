@@ -37,6 +37,10 @@ pub struct RequestQueryStringParams {
     pub offset: Option<usize>,
     /// Comma-separated list of columns to return from the POST/INSERT operation.
     pub returning_columns: Option<String>,
+    /// Maximum number of rows per INSERT batch. When unset, batching falls back to the limits
+    /// imposed by Postgres's bind-parameter cap and a fixed maximum query size, which is not
+    /// configurable.
+    pub batch_size: Option<usize>,
 }
 
 #[derive(Debug)]
@@ -105,6 +109,8 @@ pub struct QueryParamsInsert {
     pub returning_columns: Option<Vec<String>>,
     pub rows: Vec<Map<String, Value>>,
     pub table: String,
+    /// Optional per-request override of the maximum number of rows per INSERT batch.
+    pub batch_size: Option<usize>,
 }
 
 impl QueryParamsInsert {
@@ -201,6 +207,7 @@ impl QueryParamsInsert {
             returning_columns,
             rows,
             table: req.match_info().query("table").to_lowercase(),
+            batch_size: query_string_params.batch_size,
         })
     }
 }
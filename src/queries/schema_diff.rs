@@ -0,0 +1,162 @@
+use super::table_stats::get_column_stats;
+use crate::db::Connection;
+use crate::errors::ApiError;
+
+/// A foreign key declared on a desired column: the referenced `table` and `column`.
+#[derive(Clone, Debug)]
+pub struct DesiredForeignKey {
+    pub table: String,
+    pub column: String,
+}
+
+/// A single column in a user-supplied desired table definition.
+#[derive(Clone, Debug)]
+pub struct DesiredColumn {
+    pub name: String,
+    pub data_type: String,
+    pub foreign_key: Option<DesiredForeignKey>,
+}
+
+/// A user-supplied desired table definition, diffed against the live schema.
+#[derive(Clone, Debug)]
+pub struct DesiredTable {
+    pub name: String,
+    pub columns: Vec<DesiredColumn>,
+}
+
+/// The generated migration: an up-migration and a best-effort down-migration.
+#[derive(Clone, Debug)]
+pub struct Migration {
+    pub up: String,
+    pub down: String,
+}
+
+/// Compares the live Postgres schema against `desired` and returns the SQL needed to reconcile
+/// them. A table absent from the live schema is created wholesale; otherwise columns are diffed
+/// one-by-one, emitting `ADD COLUMN`, `ALTER COLUMN ... TYPE`, and `ADD CONSTRAINT ... FOREIGN KEY`
+/// statements. Type-compatibility classes keep semantically equal types (e.g. `integer`/`int4`)
+/// from producing spurious diffs.
+pub fn diff_table(conn: &Connection, desired: &DesiredTable) -> Result<Migration, ApiError> {
+    // Propagate introspection errors rather than masking them as "table absent"; an empty column
+    // set means the table genuinely does not exist yet (every live table has at least one column).
+    let live_columns = get_column_stats(conn, &desired.name)?;
+
+    if live_columns.is_empty() {
+        return Ok(create_table_migration(desired));
+    }
+
+    let mut up_statements = vec![];
+    let mut down_statements = vec![];
+
+    for desired_column in &desired.columns {
+        match live_columns
+            .iter()
+            .find(|live| live.column_name == desired_column.name)
+        {
+            None => {
+                up_statements.push(format!(
+                    "ALTER TABLE {} ADD COLUMN {} {};",
+                    desired.name, desired_column.name, desired_column.data_type,
+                ));
+                down_statements.push(format!(
+                    "ALTER TABLE {} DROP COLUMN {};",
+                    desired.name, desired_column.name,
+                ));
+            }
+            Some(live_column) => {
+                if !type_classes_match(&live_column.column_type, &desired_column.data_type) {
+                    up_statements.push(format!(
+                        "ALTER TABLE {} ALTER COLUMN {} TYPE {};",
+                        desired.name, desired_column.name, desired_column.data_type,
+                    ));
+                    // best-effort down-migration restores the previously observed type
+                    down_statements.push(format!(
+                        "ALTER TABLE {} ALTER COLUMN {} TYPE {};",
+                        desired.name, desired_column.name, live_column.column_type,
+                    ));
+                }
+            }
+        }
+
+        // Add any declared foreign key that is not already present in the live schema.
+        if let Some(foreign_key) = &desired_column.foreign_key {
+            let already_present = live_columns.iter().any(|live| {
+                live.column_name == desired_column.name
+                    && live.is_foreign_key
+                    && live.foreign_key_table.as_deref() == Some(foreign_key.table.as_str())
+            });
+
+            if !already_present {
+                let constraint_name =
+                    format!("{}_{}_fkey", desired.name, desired_column.name);
+                up_statements.push(format!(
+                    "ALTER TABLE {table} ADD CONSTRAINT {constraint} FOREIGN KEY ({column}) REFERENCES {ref_table} ({ref_column});",
+                    table = desired.name,
+                    constraint = constraint_name,
+                    column = desired_column.name,
+                    ref_table = foreign_key.table,
+                    ref_column = foreign_key.column,
+                ));
+                down_statements.push(format!(
+                    "ALTER TABLE {} DROP CONSTRAINT {};",
+                    desired.name, constraint_name,
+                ));
+            }
+        }
+    }
+
+    // down statements are applied in reverse order of the up statements
+    down_statements.reverse();
+
+    Ok(Migration {
+        up: up_statements.join("\n"),
+        down: down_statements.join("\n"),
+    })
+}
+
+/// Builds the migration to create a table that does not yet exist, including any declared foreign
+/// keys as inline column constraints.
+fn create_table_migration(desired: &DesiredTable) -> Migration {
+    let column_defs = desired
+        .columns
+        .iter()
+        .map(|column| {
+            let mut def = format!("    {} {}", column.name, column.data_type);
+            if let Some(foreign_key) = &column.foreign_key {
+                def.push_str(&format!(
+                    " REFERENCES {} ({})",
+                    foreign_key.table, foreign_key.column
+                ));
+            }
+            def
+        })
+        .collect::<Vec<String>>()
+        .join(",\n");
+
+    Migration {
+        up: format!("CREATE TABLE {} (\n{}\n);", desired.name, column_defs),
+        down: format!("DROP TABLE {};", desired.name),
+    }
+}
+
+/// Returns `true` when the live and desired type strings belong to the same compatibility class,
+/// so semantically equal types do not produce spurious diffs.
+fn type_classes_match(live_type: &str, desired_type: &str) -> bool {
+    normalize_type(live_type) == normalize_type(desired_type)
+}
+
+/// Maps a declared or introspected type string to its canonical compatibility class.
+fn normalize_type(type_str: &str) -> String {
+    let normalized = type_str.trim().to_lowercase();
+    match normalized.as_str() {
+        "integer" | "int" | "int4" => "int4".to_string(),
+        "bigint" | "int8" => "int8".to_string(),
+        "smallint" | "int2" => "int2".to_string(),
+        "text" | "varchar" | "character varying" => "text".to_string(),
+        "boolean" | "bool" => "bool".to_string(),
+        "double precision" | "float8" => "float8".to_string(),
+        "real" | "float4" => "float4".to_string(),
+        // Unknown/opaque types compare by their trimmed, lowercased form.
+        _ => normalized,
+    }
+}